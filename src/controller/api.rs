@@ -0,0 +1,133 @@
+use axum::extract::{Json, Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::controller::auth::CurrentUser;
+use crate::data::model::Post;
+
+/// Payload for `POST /api/posts`. Mirrors the shape of [`crate::controller::home::QueryParams`]
+/// under Micropub-style naming (`content`/`author`) for non-browser clients and bulk
+/// importers that drive the timeline directly as JSON rather than via the HTML form.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatePostPayload {
+    pub content: String,
+    pub author: String,
+}
+
+/// A JSON error body returned by the `/api/posts` handlers, carrying the matching HTTP
+/// status code.
+pub enum ApiError {
+    BadRequest,
+    NotFound,
+    Internal,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest => (StatusCode::BAD_REQUEST, "content must not be empty"),
+            ApiError::NotFound => (StatusCode::NOT_FOUND, "post not found"),
+            ApiError::Internal => (StatusCode::INTERNAL_SERVER_ERROR, "internal error"),
+        };
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
+
+/// Handles `POST /api/posts` -- the JSON counterpart to [`crate::controller::home::create_post`],
+/// sharing the same `PostStore` so a post made through either route shows up in the other's feed.
+///
+/// # Parameters
+///
+/// - `CurrentUser(user)`: The authenticated caller; `owner_id` is stamped from this, same as
+///   the HTML route, rather than trusted from `payload`.
+/// - `State(crate::AppState { post_manager, .. })`: Extracts the shared application state,
+///   assigning the new post an id from `post_manager`'s own counter and persisting it
+///   through the `post_manager`.
+/// - `Json(payload)`: The `content`/`author` body describing the new post.
+///
+/// # Returns
+///
+/// - `(StatusCode::CREATED, Json(post))` with the stored post on success.
+/// - `ApiError::BadRequest` if `content` is empty.
+/// - `ApiError::Internal` if persisting the post fails.
+pub async fn create_post(
+    CurrentUser(user): CurrentUser,
+    State(crate::AppState {
+              post_manager,
+              ..
+          }): State<crate::AppState>,
+    Json(payload): Json<CreatePostPayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    if payload.content.trim().is_empty() {
+        return Err(ApiError::BadRequest);
+    }
+
+    let id = post_manager.next_id().await;
+
+    let post = Post {
+        id,
+        username: payload.author.clone(),
+        message: payload.content,
+        time: crate::data::model::format_post_time(&OffsetDateTime::now_utc()),
+        avatar: format!("https://ui-avatars.com/api/?background=random&rounded=true&name= {}", payload.author),
+        owner_id: user.username,
+        revision: 0,
+    };
+
+    post_manager
+        .create_post(post.clone())
+        .await
+        .map_err(|_| ApiError::Internal)?;
+
+    Ok((StatusCode::CREATED, Json(post)))
+}
+
+/// Handles `GET /api/posts` -- the JSON counterpart to the `home` HTML route, returning the
+/// same timeline the SSE UI renders.
+///
+/// # Parameters
+///
+/// - `State(crate::AppState { post_receiver: mut receiver, .. })`: The shared watch receiver
+///   holding the current posts snapshot.
+///
+/// # Returns
+///
+/// A `Json<Vec<Post>>` of the current feed.
+pub async fn list_posts(
+    State(crate::AppState {
+              post_receiver: mut receiver,
+              ..
+          }): State<crate::AppState>,
+) -> Json<Vec<Post>> {
+    Json(receiver.borrow_and_update().posts.clone())
+}
+
+/// Handles `GET /api/posts/:id` -- looks up a single post by id.
+///
+/// # Parameters
+///
+/// - `Path(id)`: The post id from the URL path.
+/// - `State(crate::AppState { post_manager, .. })`: Extracts the shared application state.
+///
+/// # Returns
+///
+/// - `Json<Post>` if the post exists.
+/// - `ApiError::NotFound` otherwise.
+pub async fn get_post(
+    Path(id): Path<u64>,
+    State(crate::AppState {
+              post_manager,
+              ..
+          }): State<crate::AppState>,
+) -> Result<Json<Post>, ApiError> {
+    let posts = post_manager.posts();
+    let posts_lock = posts.lock().await;
+    posts_lock
+        .iter()
+        .find(|post| post.id == id)
+        .cloned()
+        .map(Json)
+        .ok_or(ApiError::NotFound)
+}
@@ -0,0 +1,130 @@
+use async_trait::async_trait;
+use axum::extract::{FromRef, FromRequestParts, State};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum_extra::extract::cookie::{Cookie, Key, SameSite, SignedCookieJar};
+use serde::{Deserialize, Serialize};
+
+use crate::controller::form_qs::JsonOrForm;
+use crate::data::users::User;
+
+/// Name of the signed cookie holding the logged-in user's id.
+const SESSION_COOKIE: &str = "session";
+
+/// Tells htmx to do a full page reload instead of swapping anything, so
+/// the signup/login forms (submitted with `hx-swap="none"`, since there's
+/// no response body to swap in) still end up re-rendering the page as the
+/// now-logged-in user once the session cookie lands.
+const HX_REFRESH: (&str, &str) = ("HX-Refresh", "true");
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignupPayload {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoginPayload {
+    pub username: String,
+    pub password: String,
+}
+
+fn session_cookie(user_id: u64) -> Cookie<'static> {
+    Cookie::build((SESSION_COOKIE, user_id.to_string()))
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .build()
+}
+
+/// Handles `POST /signup` -- creates an account with a bcrypt-hashed
+/// password and logs the new user in by issuing a signed session cookie.
+///
+/// # Parameters
+///
+/// - `State(crate::AppState { users, .. })`: Extracts the shared account registry.
+/// - `jar`: The request's signed cookie jar, returned with the new session cookie added.
+/// - `JsonOrForm(payload)`: The `username`/`password` to register.
+///
+/// # Returns
+///
+/// - `(SignedCookieJar, HX-Refresh, StatusCode::CREATED)` with the session cookie set, on success
+/// - `StatusCode::CONFLICT` if the username is already taken
+/// - `StatusCode::INTERNAL_SERVER_ERROR` if hashing or persisting the account fails
+pub async fn signup(
+    State(crate::AppState { users, .. }): State<crate::AppState>,
+    jar: SignedCookieJar,
+    JsonOrForm(payload): JsonOrForm<SignupPayload>,
+) -> Result<(SignedCookieJar, [(&'static str, &'static str); 1], StatusCode), StatusCode> {
+    let user = users
+        .signup(&payload.username, &payload.password)
+        .await
+        .map_err(|err| match err {
+            crate::data::users::SignupError::UsernameTaken => StatusCode::CONFLICT,
+            crate::data::users::SignupError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+    Ok((jar.add(session_cookie(user.id)), [HX_REFRESH], StatusCode::CREATED))
+}
+
+/// Handles `POST /login` -- verifies the submitted password against the
+/// stored bcrypt hash and, on success, issues a signed session cookie.
+///
+/// # Parameters
+///
+/// - `State(crate::AppState { users, .. })`: Extracts the shared account registry.
+/// - `jar`: The request's signed cookie jar, returned with the new session cookie added.
+/// - `JsonOrForm(payload)`: The `username`/`password` to verify.
+///
+/// # Returns
+///
+/// - `(SignedCookieJar, HX-Refresh, StatusCode::OK)` with the session cookie set, on success
+/// - `StatusCode::UNAUTHORIZED` if the username or password doesn't match
+pub async fn login(
+    State(crate::AppState { users, .. }): State<crate::AppState>,
+    jar: SignedCookieJar,
+    JsonOrForm(payload): JsonOrForm<LoginPayload>,
+) -> Result<(SignedCookieJar, [(&'static str, &'static str); 1], StatusCode), StatusCode> {
+    let user = users
+        .verify(&payload.username, &payload.password)
+        .await
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    Ok((jar.add(session_cookie(user.id)), [HX_REFRESH], StatusCode::OK))
+}
+
+/// Handles `POST /logout` -- removes the session cookie and tells htmx to
+/// reload the page so it re-renders as a signed-out visitor.
+pub async fn logout(jar: SignedCookieJar) -> (SignedCookieJar, [(&'static str, &'static str); 1], StatusCode) {
+    let removal = Cookie::build(SESSION_COOKIE).path("/").build();
+    (jar.remove(removal), [HX_REFRESH], StatusCode::OK)
+}
+
+/// Extractor pulling the logged-in [`User`] out of the request's signed
+/// session cookie. Handlers like `create_post` and `update_post` require
+/// this instead of trusting a `username` field from the request body, so
+/// `owner_id` is always stamped from who the caller actually authenticated
+/// as.
+pub struct CurrentUser(pub User);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for CurrentUser
+where
+    crate::AppState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let app_state = crate::AppState::from_ref(state);
+        let jar = SignedCookieJar::<Key>::from_headers(&parts.headers, app_state.cookie_key.clone());
+        let user_id: u64 = jar
+            .get(SESSION_COOKIE)
+            .and_then(|cookie| cookie.value().parse().ok())
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+        let user = app_state
+            .users
+            .get(user_id)
+            .await
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+        Ok(CurrentUser(user))
+    }
+}
@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use axum::extract::{Form, FromRequest, Json, Request};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::de::DeserializeOwned;
+
+/// Extracts `T` from the request body as JSON if `Content-Type` says so,
+/// falling back to a URL-encoded form body otherwise -- so the same
+/// handler serves both the app's own HTML forms (`home.rs`'s composer,
+/// `auth.rs`'s signup/login) and a JSON client posting to those same
+/// routes without two copies of each handler.
+pub struct JsonOrForm<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for JsonOrForm<T>
+where
+    T: DeserializeOwned + 'static,
+    S: Send + Sync,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let is_json = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|content_type| content_type.starts_with("application/json"));
+
+        if is_json {
+            let Json(payload) = Json::<T>::from_request(req, state)
+                .await
+                .map_err(|rejection| rejection.into_response().status())?;
+            Ok(JsonOrForm(payload))
+        } else {
+            let Form(payload) = Form::<T>::from_request(req, state)
+                .await
+                .map_err(|rejection| rejection.into_response().status())?;
+            Ok(JsonOrForm(payload))
+        }
+    }
+}
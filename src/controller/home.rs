@@ -1,23 +1,38 @@
 use std::sync::mpsc::RecvError;
 use axum::extract::{State, Path};
-use axum::http::StatusCode;
+use axum::http::{StatusCode, header};
 use axum::response::{Html, IntoResponse, Sse};
 use axum::response::sse::{Event, KeepAlive};
-use fake::Fake;
-use fake::faker::internet::en::Username;
 use futures::Stream;
+use rss::{ChannelBuilder, GuidBuilder, ItemBuilder};
 use tokio_stream::wrappers::ReceiverStream;
 use crate::data::model::Post;
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
+use crate::controller::auth::CurrentUser;
 use crate::controller::form_qs::JsonOrForm;
-use crate::views::home::{home_page, edit_form, post_html};
+use crate::data::posts_datasource::{diff_posts, PostDelta};
+use crate::views::home::{home_page, edit_form, post_html, post_created_oob, post_updated_oob, post_deleted_oob};
 use html_node::{html, text};
+use std::collections::HashMap;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct QueryParams {
-    username: String,
-    message: String,
+    pub username: String,
+    pub message: String,
+}
+
+/// Payload for `PUT /posts/:id`. `base_revision`/`base_message` are hidden
+/// fields `edit_form` stamps with the post as it was when the form was
+/// opened, so the server can reject the submission as a conflict if the
+/// post's revision has moved on since, rather than blindly overwriting
+/// whatever is there now.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EditPayload {
+    pub username: String,
+    pub message: String,
+    pub base_message: String,
+    pub base_revision: u64,
 }
 
 /// Renders the home page as an HTML response, dynamically generating its content
@@ -25,6 +40,9 @@ pub struct QueryParams {
 ///
 /// # Parameters
 ///
+/// - `current_user`: The caller's session, if any. `create_post` requires a
+///   signed-in session, so a visitor with no session cookie sees the
+///   signup/login forms here instead of a composer they couldn't use.
 /// - `State(crate::AppState { post_receiver: mut receiver, .. })`:
 ///   Extracts the shared application state containing a watch receiver (`post_receiver`)
 ///   that holds the current posts data. The receiver allows access to the latest updates
@@ -34,84 +52,182 @@ pub struct QueryParams {
 ///
 /// - An `Html<String>` response containing the rendered home page with the latest posts data.
 pub async fn home(
+    current_user: Option<CurrentUser>,
     State(crate::AppState {
               post_receiver: mut receiver,
+              post_manager,
               ..
           }): State<crate::AppState>,
 ) -> Html<String> {
-    let username: String = Username().fake();
-    let content = home_page(&username, receiver.borrow_and_update());
+    let username = current_user.as_ref().map(|CurrentUser(user)| user.username.as_str());
+    let content = home_page(username, &receiver.borrow_and_update().posts, post_manager.store_id());
     Html(content)
 }
 
-/// Handles a Server-Sent Events (SSE) stream for the home page, sending updated HTML content
-/// whenever the application's post data changes.
+/// Handles a Server-Sent Events (SSE) stream for the home page, sending
+/// one named event per changed post instead of re-rendering the whole
+/// page.
 ///
 /// # Parameters
 ///
-/// - `State(crate::AppState { post_receiver: mut _receiver, .. })`:
+/// - `State(crate::AppState { post_receiver: mut _receiver, shutdown, .. })`:
 ///   Extracts the shared application state containing a watch receiver (`post_receiver`)
-///   that monitors changes to the `posts` data. The receiver is used to detect updates and send new content.
+///   that monitors changes to the `posts` data, and the [`crate::data::shutdown::Shutdown`]
+///   handle the background task is spawned and tracked through so it exits cleanly on
+///   SIGINT/SIGTERM instead of being killed mid-stream.
 ///
 /// # Returns
 ///
-/// An `Sse` stream that sends updated HTML content as events to the client.
-/// Each event contains the serialized HTML for the home page when the `posts` data changes.
+/// An `Sse` stream of `post-created`/`post-updated`/`post-deleted` events,
+/// each carrying an HTMX out-of-band fragment sized to just the post that
+/// changed, regardless of how long the feed has grown.
 pub async fn home_sse(
     State(crate::AppState {
               post_receiver: mut _receiver,
+              shutdown,
+              post_manager,
               ..
           }): State<crate::AppState>,
 ) -> Sse<impl Stream<Item=Result<Event, RecvError>>> {
-    let username: String = Username().fake();
     let (sender, receiver1) = tokio::sync::mpsc::channel(1);
-    tokio::task::spawn(async move {
+    let cancelled = shutdown.token();
+    let store_id = post_manager.store_id();
+    shutdown.spawn(async move {
+        // The first snapshot observed is the one the client's initial page
+        // render already reflects, so it seeds `last` without emitting any
+        // events -- otherwise every pre-existing post would be broadcast
+        // again as a spurious `post-created`. A post that lands in the
+        // narrow window between that render and this connect is folded
+        // into the baseline and missed rather than duplicated; it'll show
+        // up once a later change triggers a real diff.
+        let mut last: Option<HashMap<u64, u64>> = None;
         loop {
-            if _receiver.changed().await.is_err() {
-                println!("Post Receiver disconnected");
-                return;
-            }
+            tokio::select! {
+                _ = cancelled.cancelled() => {
+                    return;
+                }
+                changed = _receiver.changed() => {
+                    if changed.is_err() {
+                        println!("Post Receiver disconnected");
+                        return;
+                    }
 
-            let html = home_page(&username, _receiver.borrow_and_update());
-            if let Err(err) = sender.send(Ok(Event::default().data(html))).await {
-                println!("Failed to send event: {}", err);
-                return;
+                    let posts = _receiver.borrow_and_update().posts.clone();
+                    let (deltas, next) = diff_posts(last.as_ref().unwrap_or(&HashMap::new()), &posts);
+                    let seeding = last.is_none();
+                    last = Some(next);
+                    if seeding {
+                        continue;
+                    }
+
+                    for delta in deltas {
+                        let event = match delta {
+                            PostDelta::Created(post) => Event::default().event("post-created").data(post_created_oob(&post, store_id)),
+                            PostDelta::Updated(post) => Event::default().event("post-updated").data(post_updated_oob(&post, store_id)),
+                            PostDelta::Deleted(id) => Event::default().event("post-deleted").data(post_deleted_oob(id)),
+                        };
+                        if let Err(err) = sender.send(Ok(event)).await {
+                            println!("Failed to send event: {}", err);
+                            return;
+                        }
+                    }
+                }
             }
         }
-    });
+    }).await;
     Sse::new(ReceiverStream::new(receiver1)).keep_alive(KeepAlive::default())
 }
 
+/// Serves the post timeline as an RSS 2.0 feed.
+///
+/// # Parameters
+///
+/// - `State(crate::AppState { post_receiver: mut receiver, .. })`:
+///   Extracts the shared application state containing the watch receiver
+///   that holds the current posts snapshot. The same snapshot the home
+///   page renders from is used to build the feed, so the two never drift.
+///
+/// # Returns
+///
+/// The channel serialized as RSS XML with an `application/rss+xml`
+/// content type.
+pub async fn feed(
+    State(crate::AppState {
+              post_receiver: mut receiver,
+              ..
+          }): State<crate::AppState>,
+) -> impl IntoResponse {
+    let posts = receiver.borrow_and_update().posts.clone();
+
+    let items = posts
+        .iter()
+        .map(|post| {
+            ItemBuilder::default()
+                .title(Some(format!("{}: {}", post.username, post.message)))
+                .description(Some(post.message.clone()))
+                .author(Some(post.username.clone()))
+                .pub_date(Some(post.time.clone()))
+                .guid(Some(
+                    GuidBuilder::default()
+                        .value(post.id.to_string())
+                        .permalink(false)
+                        .build(),
+                ))
+                .build()
+        })
+        .collect::<Vec<_>>();
+
+    let channel = ChannelBuilder::default()
+        .title("htmx-twitter")
+        .link("http://localhost:8080/home")
+        .description("A Twitter clone in htmx and Rust")
+        .items(items)
+        .build();
+
+    (
+        [(header::CONTENT_TYPE, "application/rss+xml")],
+        channel.to_string(),
+    )
+}
+
 /// Handles the creation of a new post and adds it to the shared application state.
 ///
 /// # Parameters
 ///
-/// - `State(crate::AppState { posts: state, .. })`: Extracts the shared application state containing
-///   the `posts` vector, which is protected by a `Mutex`. The `State` wrapper allows for dependency injection
-///   of the app state.
+/// - `CurrentUser(user)`: The authenticated caller, resolved from the session cookie.
+///   `owner_id` is stamped from this rather than trusted from `payload`, so a post can
+///   never be created on someone else's behalf.
+/// - `State(crate::AppState { post_manager, .. })`: Extracts the shared application state,
+///   assigning the new post an id from `post_manager`'s own counter and persisting it
+///   through the `post_manager`.
 /// - `JsonOrForm(payload)`: Parses the incoming request body as either JSON or a form payload, extracting
 ///   the `QueryParams` structure that contains the `username` and `message` for the new post.
 ///
 /// # Returns
 ///
 /// - `Ok(StatusCode::OK)` if the post is successfully created and added to the shared state.
-/// - `Result` is used to handle potential errors, though the current implementation does not anticipate any.
+/// - `StatusCode::UNAUTHORIZED` if the caller has no valid session.
 pub async fn create_post(
+    CurrentUser(user): CurrentUser,
     State(crate::AppState {
-              posts: state,
+              post_manager,
               ..
           }): State<crate::AppState>,
     JsonOrForm(payload): JsonOrForm<QueryParams>,
 ) -> Result<impl IntoResponse, StatusCode> {
-    let mut posts_lock = state.lock().await; // Lock the Mutex
-    let id = posts_lock.len(); // Simple ID assignment based on current length
-    posts_lock.push(Post {
-        id,
-        username: payload.username.to_string(),
-        message: payload.message.to_string(),
-        time: OffsetDateTime::now_utc().to_string(),
-        avatar: format!("https://ui-avatars.com/api/?background=random&rounded=true&name= {}", payload.username.to_string()),
-    });
+    let id = post_manager.next_id().await;
+    post_manager
+        .create_post(Post {
+            id,
+            username: payload.username.to_string(),
+            message: payload.message.to_string(),
+            time: crate::data::model::format_post_time(&OffsetDateTime::now_utc()),
+            avatar: format!("https://ui-avatars.com/api/?background=random&rounded=true&name= {}", payload.username),
+            owner_id: user.username,
+            revision: 0,
+        })
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     Ok(StatusCode::OK)
 }
 
@@ -120,21 +236,22 @@ pub async fn create_post(
 /// # Parameters
 ///
 /// - `Path(id)`: Extracts the post ID from the URL path
-/// - `State(crate::AppState { posts: state, .. })`: Extracts the shared application state
+/// - `State(crate::AppState { post_manager, .. })`: Extracts the shared application state
 ///
 /// # Returns
 ///
 /// - `Html<String>` containing the edit form if the post exists
 /// - `StatusCode::NOT_FOUND` if the post doesn't exist
 pub async fn edit_post(
-    Path(id): Path<usize>,
+    Path(id): Path<u64>,
     State(crate::AppState {
-              posts: state,
+              post_manager,
               ..
           }): State<crate::AppState>,
 ) -> Result<Html<String>, StatusCode> {
-    let posts_lock = state.lock().await;
-    
+    let posts = post_manager.posts();
+    let posts_lock = posts.lock().await;
+
     if let Some(post) = posts_lock.iter().find(|p| p.id == id) {
         let form_html = edit_form(post);
         Ok(Html(form_html))
@@ -143,45 +260,116 @@ pub async fn edit_post(
     }
 }
 
-/// Handles PUT /posts/:id - updates an existing post
+/// Handles PUT /posts/:id - applies an edit to an existing post, rejecting
+/// it as a conflict unless `base_revision` is exactly the post's current
+/// revision (plain compare-and-swap; see [`crate::data::post_manager::PostManager::apply_edit`]
+/// for why this doesn't try to merge concurrent edits).
 ///
 /// # Parameters
 ///
+/// - `CurrentUser(user)`: The authenticated caller; the edit is rejected with `403` unless
+///   this matches the post's `owner_id`.
 /// - `Path(id)`: Extracts the post ID from the URL path
-/// - `State(crate::AppState { posts: state, .. })`: Extracts the shared application state
-/// - `JsonOrForm(payload)`: Parses the incoming request body
+/// - `State(crate::AppState { post_manager, .. })`: Extracts the shared application state
+/// - `JsonOrForm(payload)`: The edited message plus the base revision/message it was edited from
 ///
 /// # Returns
 ///
-/// - `Html<String>` containing the updated post HTML if successful
+/// - `(StatusCode::OK, Html<String>)` with the updated post HTML if the edit applied cleanly
+/// - `(StatusCode::CONFLICT, Html<String>)` with the current post HTML if `base_revision` is
+///   no longer current -- the caller should refetch and retry against it
 /// - `StatusCode::NOT_FOUND` if the post doesn't exist
+/// - `StatusCode::FORBIDDEN` if the caller doesn't own the post
 /// - `StatusCode::BAD_REQUEST` if validation fails
 pub async fn update_post(
-    Path(id): Path<usize>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<u64>,
     State(crate::AppState {
-              posts: state,
+              post_manager,
               ..
           }): State<crate::AppState>,
-    JsonOrForm(payload): JsonOrForm<QueryParams>,
-) -> Result<Html<String>, StatusCode> {
+    JsonOrForm(payload): JsonOrForm<EditPayload>,
+) -> Result<(StatusCode, Html<String>), StatusCode> {
     // Validate input
     if payload.message.trim().is_empty() {
         return Err(StatusCode::BAD_REQUEST);
     }
 
-    let mut posts_lock = state.lock().await;
-    
-    if let Some(post) = posts_lock.iter_mut().find(|p| p.id == id) {
-        // Update the post
-        post.message = payload.message.to_string();
-        post.username = payload.username.to_string();
-        // Keep the original time and avatar
-        
-        // Return the updated post HTML
-        let updated_post_html = post_html(post);
-        Ok(Html(updated_post_html.to_string()))
-    } else {
-        Err(StatusCode::NOT_FOUND)
+    {
+        let posts = post_manager.posts();
+        let posts_lock = posts.lock().await;
+        if let Some(post) = posts_lock.iter().find(|p| p.id == id) {
+            if post.owner_id != user.username {
+                return Err(StatusCode::FORBIDDEN);
+            }
+        }
+    }
+
+    let edit = crate::data::ot::OtEdit {
+        base_revision: payload.base_revision,
+        ops: vec![
+            crate::data::ot::OtOp::Delete(payload.base_message.chars().count()),
+            crate::data::ot::OtOp::Insert(payload.message.clone()),
+        ],
+    };
+
+    match post_manager.apply_edit(id, edit).await {
+        Ok(post) => Ok((StatusCode::OK, Html(post_html(&post, post_manager.store_id()).to_string()))),
+        Err(crate::data::post_manager::ApplyEditError::NotFound) => Err(StatusCode::NOT_FOUND),
+        Err(crate::data::post_manager::ApplyEditError::Stale) => {
+            let posts = post_manager.posts();
+            let posts_lock = posts.lock().await;
+            let current = posts_lock
+                .iter()
+                .find(|p| p.id == id)
+                .ok_or(StatusCode::NOT_FOUND)?;
+            Ok((StatusCode::CONFLICT, Html(post_html(current, post_manager.store_id()).to_string())))
+        }
+        Err(crate::data::post_manager::ApplyEditError::Io(_)) => {
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Handles DELETE /posts/:id - removes a post the caller owns.
+///
+/// # Parameters
+///
+/// - `CurrentUser(user)`: The authenticated caller; the deletion is rejected with `403`
+///   unless this matches the post's `owner_id`.
+/// - `Path(id)`: Extracts the post ID from the URL path
+/// - `State(crate::AppState { post_manager, .. })`: Extracts the shared application state
+///
+/// # Returns
+///
+/// - `StatusCode::OK` with an empty body once the post is removed, so HTMX can swap
+///   the card out of the page.
+/// - `StatusCode::NOT_FOUND` if the post doesn't exist.
+/// - `StatusCode::FORBIDDEN` if the caller doesn't own the post.
+pub async fn delete_post(
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<u64>,
+    State(crate::AppState {
+              post_manager,
+              ..
+          }): State<crate::AppState>,
+) -> Result<StatusCode, StatusCode> {
+    {
+        let posts = post_manager.posts();
+        let posts_lock = posts.lock().await;
+        let post = posts_lock.iter().find(|p| p.id == id).ok_or(StatusCode::NOT_FOUND)?;
+        if post.owner_id != user.username {
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
+
+    match post_manager.delete_post(id).await {
+        Ok(true) => {
+            crate::views::markdown::forget_post(post_manager.store_id(), id);
+            Ok(StatusCode::OK)
+        }
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
 
@@ -190,21 +378,22 @@ pub async fn update_post(
 /// # Parameters
 ///
 /// - `Path(id)`: Extracts the post ID from the URL path
-/// - `State(crate::AppState { posts: state, .. })`: Extracts the shared application state
+/// - `State(crate::AppState { post_manager, .. })`: Extracts the shared application state
 ///
 /// # Returns
 ///
 /// - `Html<String>` containing the original post content if the post exists
 /// - `StatusCode::NOT_FOUND` if the post doesn't exist
 pub async fn cancel_edit_post(
-    Path(id): Path<usize>,
+    Path(id): Path<u64>,
     State(crate::AppState {
-              posts: state,
+              post_manager,
               ..
           }): State<crate::AppState>,
 ) -> Result<Html<String>, StatusCode> {
-    let posts_lock = state.lock().await;
-    
+    let posts = post_manager.posts();
+    let posts_lock = posts.lock().await;
+
     if let Some(post) = posts_lock.iter().find(|p| p.id == id) {
         let post_content_html = html! {
             <div class="card-text lead mb-2" id={text!("post-content-{}", post.id)}>
@@ -0,0 +1,84 @@
+use axum::body::Body;
+use axum::extract::{Multipart, Path, State};
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use futures::TryStreamExt;
+use tokio_util::io::{ReaderStream, StreamReader};
+
+use crate::controller::auth::CurrentUser;
+use crate::data::media_store::WriteMediaError;
+
+/// Handles `POST /media`, streaming the first multipart field's bytes
+/// straight into the configured `MediaStore` rather than buffering the
+/// upload in memory first.
+///
+/// # Parameters
+///
+/// - `CurrentUser(_)`: The authenticated caller; uploads are rejected with `401`
+///   for anonymous requests, same as `create_post`, so the size cap can't be
+///   used as an unauthenticated disk-filling vector.
+/// - `State(crate::AppState { media_store, .. })`: Extracts the shared media store.
+/// - `multipart`: The incoming multipart body; only its first field is read,
+///   with any remaining fields drained afterward so the connection can be
+///   reused.
+///
+/// # Returns
+///
+/// - `(StatusCode::CREATED, url)` with the stored media's URL as the
+///   plain-text body, for use as a post's `avatar` or an inline
+///   attachment.
+/// - `StatusCode::BAD_REQUEST` if the body has no fields.
+/// - `StatusCode::PAYLOAD_TOO_LARGE` if the field exceeds
+///   [`crate::data::media_store::MAX_MEDIA_BYTES`].
+pub async fn upload_media(
+    CurrentUser(_): CurrentUser,
+    State(crate::AppState { media_store, .. }): State<crate::AppState>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, StatusCode> {
+    let field = multipart
+        .next_field()
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?
+        .ok_or(StatusCode::BAD_REQUEST)?;
+
+    let stream = field.map_err(|err| std::io::Error::other(err.to_string()));
+    let mut reader = StreamReader::new(stream);
+
+    let result = media_store.write(&mut reader).await;
+
+    // Drain any remaining fields so the connection can be reused even if
+    // the caller sent more than one (only the first is stored).
+    while multipart.next_field().await.unwrap_or(None).is_some() {}
+
+    match result {
+        Ok(stored) => Ok((StatusCode::CREATED, stored.url)),
+        Err(WriteMediaError::TooLarge) => Err(StatusCode::PAYLOAD_TOO_LARGE),
+        Err(WriteMediaError::Io(_)) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Handles `GET /media/:id`, streaming a stored blob back with its
+/// sniffed content type.
+///
+/// # Parameters
+///
+/// - `Path(id)`: The content hash returned by [`upload_media`].
+/// - `State(crate::AppState { media_store, .. })`: Extracts the shared media store.
+///
+/// # Returns
+///
+/// - The blob's bytes, streamed, with a matching `Content-Type`.
+/// - `StatusCode::NOT_FOUND` if no blob is stored under `id`.
+pub async fn get_media(
+    Path(id): Path<String>,
+    State(crate::AppState { media_store, .. }): State<crate::AppState>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let (content_type, reader) = media_store
+        .read(&id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let body = Body::from_stream(ReaderStream::new(reader));
+    Ok(([(header::CONTENT_TYPE, content_type)], body))
+}
@@ -0,0 +1,6 @@
+pub mod api;
+pub mod auth;
+pub mod form_qs;
+pub mod home;
+pub mod media;
+pub mod rooms;
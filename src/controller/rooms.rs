@@ -0,0 +1,165 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive};
+use axum::response::{Html, IntoResponse, Sse};
+use futures::Stream;
+use std::collections::HashMap;
+use std::sync::mpsc::RecvError;
+use time::OffsetDateTime;
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::controller::auth::CurrentUser;
+use crate::controller::form_qs::JsonOrForm;
+use crate::controller::home::QueryParams;
+use crate::data::model::Post;
+use crate::data::posts_datasource::{diff_posts, PostDelta};
+use crate::views::home::{home_page, post_created_oob, post_deleted_oob, post_updated_oob};
+
+/// Renders a room's timeline as an HTML response.
+///
+/// # Parameters
+///
+/// - `Path(room)`: The room name from `/rooms/:room`.
+/// - `current_user`: The caller's session, if any -- as on the home
+///   timeline, a visitor with no session sees the signup/login forms
+///   instead of a composer `create_room_post` would just 401 on.
+/// - `State(crate::AppState { rooms, .. })`: Extracts the room registry,
+///   lazily loading `room`'s `PostManager` on first visit.
+///
+/// # Returns
+///
+/// An `Html<String>` response containing the room's rendered timeline.
+pub async fn room_home(
+    Path(room): Path<String>,
+    current_user: Option<CurrentUser>,
+    State(crate::AppState { rooms, .. }): State<crate::AppState>,
+) -> Result<Html<String>, StatusCode> {
+    let manager = rooms
+        .get_or_create(&room)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let username = current_user.as_ref().map(|CurrentUser(user)| user.username.as_str());
+    let mut receiver = manager.receiver();
+    let content = home_page(username, &receiver.borrow_and_update().posts, manager.store_id());
+    Ok(Html(content))
+}
+
+/// Streams a room's timeline over Server-Sent Events, mirroring
+/// [`crate::controller::home::home_sse`] but scoped to a single room:
+/// rather than re-rendering the whole page, it diffs successive snapshots
+/// and emits one named `post-created`/`post-updated`/`post-deleted` event
+/// per changed post.
+///
+/// # Parameters
+///
+/// - `Path(room)`: The room name from `/rooms/:room/sse`.
+/// - `State(crate::AppState { rooms, shutdown, .. })`: Extracts the room
+///   registry and the [`crate::data::shutdown::Shutdown`] handle the
+///   background task is spawned and tracked through so it exits cleanly
+///   on SIGINT/SIGTERM instead of being killed mid-stream.
+///
+/// # Returns
+///
+/// An `Sse` stream of per-post HTMX out-of-band fragments.
+pub async fn room_sse(
+    Path(room): Path<String>,
+    State(crate::AppState { rooms, shutdown, .. }): State<crate::AppState>,
+) -> Result<Sse<impl Stream<Item = Result<Event, RecvError>>>, StatusCode> {
+    let manager = rooms
+        .get_or_create(&room)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let mut receiver = manager.receiver();
+    let store_id = manager.store_id();
+    let (sender, receiver1) = tokio::sync::mpsc::channel(1);
+    let cancelled = shutdown.token();
+    shutdown.spawn(async move {
+        // See home_sse: the first snapshot seeds `last` without emitting
+        // events, since the client's initial page render already reflects
+        // it (a post landing in the connect race is folded into the
+        // baseline and missed rather than duplicated).
+        let mut last: Option<HashMap<u64, u64>> = None;
+        loop {
+            tokio::select! {
+                _ = cancelled.cancelled() => {
+                    return;
+                }
+                changed = receiver.changed() => {
+                    if changed.is_err() {
+                        println!("Room post receiver disconnected");
+                        return;
+                    }
+
+                    let posts = receiver.borrow_and_update().posts.clone();
+                    let (deltas, next) = diff_posts(last.as_ref().unwrap_or(&HashMap::new()), &posts);
+                    let seeding = last.is_none();
+                    last = Some(next);
+                    if seeding {
+                        continue;
+                    }
+
+                    for delta in deltas {
+                        let event = match delta {
+                            PostDelta::Created(post) => Event::default().event("post-created").data(post_created_oob(&post, store_id)),
+                            PostDelta::Updated(post) => Event::default().event("post-updated").data(post_updated_oob(&post, store_id)),
+                            PostDelta::Deleted(id) => Event::default().event("post-deleted").data(post_deleted_oob(id)),
+                        };
+                        if let Err(err) = sender.send(Ok(event)).await {
+                            println!("Failed to send event: {}", err);
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }).await;
+    Ok(Sse::new(ReceiverStream::new(receiver1)).keep_alive(KeepAlive::default()))
+}
+
+/// Handles `POST /rooms/:room`, publishing the new post only to that
+/// room's subscribers.
+///
+/// # Parameters
+///
+/// - `CurrentUser(user)`: The authenticated caller; `owner_id` is stamped from this
+///   rather than trusted from `payload`.
+/// - `Path(room)`: The room to publish into.
+/// - `State(crate::AppState { rooms, .. })`: Extracts the room registry; the new post's id
+///   comes from that room's own `PostManager` counter, so each room's ids are independent.
+/// - `JsonOrForm(payload)`: The `username`/`message` payload.
+///
+/// # Returns
+///
+/// `StatusCode::OK` once the post has been persisted and broadcast to the
+/// room.
+pub async fn create_room_post(
+    CurrentUser(user): CurrentUser,
+    Path(room): Path<String>,
+    State(crate::AppState { rooms, .. }): State<crate::AppState>,
+    JsonOrForm(payload): JsonOrForm<QueryParams>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let manager = rooms
+        .get_or_create(&room)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let id = manager.next_id().await;
+
+    manager
+        .create_post(Post {
+            id,
+            username: payload.username.to_string(),
+            message: payload.message.to_string(),
+            time: crate::data::model::format_post_time(&OffsetDateTime::now_utc()),
+            avatar: format!(
+                "https://ui-avatars.com/api/?background=random&rounded=true&name= {}",
+                payload.username
+            ),
+            owner_id: user.username,
+            revision: 0,
+        })
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(StatusCode::OK)
+}
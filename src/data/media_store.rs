@@ -0,0 +1,217 @@
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+/// Rejects an upload outright once it has streamed this many bytes,
+/// rather than buffering an unbounded body in memory or on disk.
+pub const MAX_MEDIA_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Why a [`MediaStore::write`] was rejected.
+#[derive(Debug)]
+pub enum WriteMediaError {
+    /// The body exceeded [`MAX_MEDIA_BYTES`] before it finished streaming.
+    TooLarge,
+    Io(anyhow::Error),
+}
+
+/// A blob that has been durably stored, as returned by [`MediaStore::write`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredMedia {
+    /// Content hash, also the id [`MediaStore::read`] looks up by.
+    pub id: String,
+    /// Sniffed from the bytes themselves, not trusted from the uploader.
+    pub content_type: String,
+    /// Path clients dereference to fetch the blob back, e.g. `/media/<id>`.
+    pub url: String,
+}
+
+/// Abstracts where uploaded media (avatars, inline attachments) lives,
+/// mirroring [`crate::data::post_store::PostStore`]'s role for posts:
+/// callers stream bytes in and get a content-addressed id back, without
+/// caring whether the backing store is the filesystem or something else.
+///
+/// Both methods stream rather than take a `Vec<u8>`, so a large upload
+/// never needs to be buffered whole in memory.
+#[async_trait]
+pub trait MediaStore: Send + Sync {
+    /// Streams `body` into the store, hashing it as it goes so the
+    /// content itself becomes the id. Rejects the upload with
+    /// [`WriteMediaError::TooLarge`] as soon as it crosses
+    /// [`MAX_MEDIA_BYTES`], without finishing the read.
+    async fn write(
+        &self,
+        body: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<StoredMedia, WriteMediaError>;
+
+    /// Opens the blob stored under `id` for streaming, alongside its
+    /// sniffed content type. `None` if no blob exists under that id.
+    async fn read(
+        &self,
+        id: &str,
+    ) -> anyhow::Result<Option<(String, Pin<Box<dyn AsyncRead + Send>>)>>;
+}
+
+/// Sniffs a content type from the handful of magic bytes every common
+/// image format starts with, rather than trusting the `Content-Type`
+/// header an uploader sent. Falls back to a generic binary type for
+/// anything unrecognized.
+fn sniff_content_type(head: &[u8]) -> &'static str {
+    if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+        "image/png"
+    } else if head.starts_with(b"\xff\xd8\xff") {
+        "image/jpeg"
+    } else if head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a") {
+        "image/gif"
+    } else if head.len() >= 12 && &head[0..4] == b"RIFF" && &head[8..12] == b"WEBP" {
+        "image/webp"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+/// A filesystem-safe temp filename that won't collide across concurrent
+/// uploads, without pulling in a UUID crate for something this small.
+fn temp_file_name() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let pid = std::process::id();
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{pid}-{n}")
+}
+
+/// Stores blobs under `root` at a path derived from their content hash
+/// (`<first 2 hex chars>/<rest>`, the same sharding `git` uses for loose
+/// objects), so no separate id allocator or index is needed and two
+/// uploads of identical bytes collapse onto one file.
+pub struct FileMediaStore {
+    root: PathBuf,
+    base_url: String,
+}
+
+impl FileMediaStore {
+    /// Ensures `root` (and its `tmp` staging subdirectory) exists.
+    /// `base_url` is prefixed onto an id to build the public URL returned
+    /// by [`MediaStore::write`], e.g. `/media`.
+    pub async fn new(root: impl Into<PathBuf>, base_url: impl Into<String>) -> anyhow::Result<Self> {
+        let root = root.into();
+        tokio::fs::create_dir_all(root.join("tmp")).await?;
+        Ok(FileMediaStore {
+            root,
+            base_url: base_url.into(),
+        })
+    }
+
+    /// The on-disk path for a content hash, valid whether or not the file
+    /// exists yet. Returns `None` if `id` isn't a plausible hash, so a
+    /// caller can't path-traverse out of `root` via a crafted id.
+    fn path_for(&self, id: &str) -> Option<PathBuf> {
+        if id.len() < 3 || !id.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        Some(self.root.join(&id[0..2]).join(&id[2..]))
+    }
+}
+
+#[async_trait]
+impl MediaStore for FileMediaStore {
+    async fn write(
+        &self,
+        body: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<StoredMedia, WriteMediaError> {
+        let tmp_path = self.root.join("tmp").join(temp_file_name());
+        let mut tmp_file = tokio::fs::File::create(&tmp_path)
+            .await
+            .map_err(|err| WriteMediaError::Io(err.into()))?;
+
+        let mut hasher = Sha256::new();
+        let mut total: u64 = 0;
+        let mut buf = [0u8; 64 * 1024];
+        loop {
+            let read = body
+                .read(&mut buf)
+                .await
+                .map_err(|err| WriteMediaError::Io(err.into()))?;
+            if read == 0 {
+                break;
+            }
+            total += read as u64;
+            if total > MAX_MEDIA_BYTES {
+                let _ = tokio::fs::remove_file(&tmp_path).await;
+                return Err(WriteMediaError::TooLarge);
+            }
+            hasher.update(&buf[..read]);
+            tmp_file
+                .write_all(&buf[..read])
+                .await
+                .map_err(|err| WriteMediaError::Io(err.into()))?;
+        }
+        tmp_file
+            .flush()
+            .await
+            .map_err(|err| WriteMediaError::Io(err.into()))?;
+
+        let mut head = [0u8; 16];
+        let mut preview = tokio::fs::File::open(&tmp_path)
+            .await
+            .map_err(|err| WriteMediaError::Io(err.into()))?;
+        let head_len = preview
+            .read(&mut head)
+            .await
+            .map_err(|err| WriteMediaError::Io(err.into()))?;
+        let content_type = sniff_content_type(&head[..head_len]).to_string();
+
+        let id = format!("{:x}", hasher.finalize());
+        let Some(final_path) = self.path_for(&id) else {
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+            return Err(WriteMediaError::Io(anyhow::anyhow!(
+                "hashed id was not a valid path component"
+            )));
+        };
+        if let Some(parent) = final_path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|err| WriteMediaError::Io(err.into()))?;
+        }
+        if tokio::fs::try_exists(&final_path)
+            .await
+            .map_err(|err| WriteMediaError::Io(err.into()))?
+        {
+            // Identical content already stored under this hash; drop the
+            // duplicate upload rather than overwrite it.
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+        } else {
+            tokio::fs::rename(&tmp_path, &final_path)
+                .await
+                .map_err(|err| WriteMediaError::Io(err.into()))?;
+        }
+
+        Ok(StoredMedia {
+            url: format!("{}/{}", self.base_url, id),
+            id,
+            content_type,
+        })
+    }
+
+    async fn read(
+        &self,
+        id: &str,
+    ) -> anyhow::Result<Option<(String, Pin<Box<dyn AsyncRead + Send>>)>> {
+        let Some(path) = self.path_for(id) else {
+            return Ok(None);
+        };
+        if !tokio::fs::try_exists(&path).await? {
+            return Ok(None);
+        }
+
+        let mut file = tokio::fs::File::open(&path).await?;
+        let mut head = [0u8; 16];
+        let head_len = file.read(&mut head).await?;
+        let content_type = sniff_content_type(&head[..head_len]).to_string();
+        file.seek(std::io::SeekFrom::Start(0)).await?;
+
+        Ok(Some((content_type, Box::pin(file))))
+    }
+}
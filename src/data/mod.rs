@@ -0,0 +1,9 @@
+pub mod media_store;
+pub mod model;
+pub mod ot;
+pub mod posts_datasource;
+pub mod post_manager;
+pub mod post_store;
+pub mod rooms;
+pub mod shutdown;
+pub mod users;
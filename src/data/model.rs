@@ -1,4 +1,15 @@
-#[derive(Default, Clone, Debug, PartialEq, Hash)]
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+/// Formats `time` the way every `Post::time` is stored, in RFC 2822 --
+/// readable for display, and already the format RSS 2.0 `pubDate` requires,
+/// so [`crate::controller::home::feed`] doesn't have to reparse it.
+pub fn format_post_time(time: &OffsetDateTime) -> String {
+    time.format(&time::format_description::well_known::Rfc2822)
+        .unwrap_or_else(|_| time.to_string())
+}
+
+#[derive(Default, Clone, Debug, PartialEq, Hash, Serialize, Deserialize)]
 pub struct Post {
     pub id: u64,
     pub username: String,
@@ -6,4 +17,5 @@ pub struct Post {
     pub time: String,
     pub avatar: String,
     pub owner_id: String, // Simple string-based owner identification
+    pub revision: u64, // Bumped on every accepted edit; an edit is rejected as a conflict unless its base_revision matches this exactly
 }
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+// This module's name and the presence of `Retain`/`Insert`/`Delete` still
+// suggest real operational transform, but there isn't one here: nothing in
+// this codebase ever transforms a concurrent edit's ops against another's
+// (there is no `transform(a, b) -> (a', b')`), and no op history is kept.
+// `PostManager::apply_edit` rejects any edit whose `base_revision` isn't
+// exactly current instead, which was the right call once it turned out
+// every caller only ever submits a full-document `Delete(len)+Insert(msg)`
+// "edit" -- but it means a second person's edit is simply dropped (as a
+// `409` the caller must retry against) rather than merged, which is what
+// real OT would buy you. Treat this as a compare-and-swap with a vestigial
+// OT-shaped wire format, not as operational transform.
+
+/// A single operational-transform primitive against a UTF-8 document,
+/// counted in chars (not bytes) so multi-byte text transforms correctly.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum OtOp {
+    /// Leave the next `n` chars of the base document untouched.
+    Retain(usize),
+    /// Insert this text at the current cursor position.
+    Insert(String),
+    /// Remove the next `n` chars of the base document.
+    Delete(usize),
+}
+
+/// An edit against a post's message: an ordered list of [`OtOp`]s plus the
+/// revision of the document they were computed against.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OtEdit {
+    pub base_revision: u64,
+    pub ops: Vec<OtOp>,
+}
+
+/// Applies `ops` to `base`, returning the resulting document.
+///
+/// Chars of `base` left over after the last op are copied through
+/// unchanged. Panics if a `Retain` or `Delete` reaches past the end of
+/// `base` -- callers are expected to validate ops against the revision
+/// they were computed from before calling this.
+pub fn apply(base: &str, ops: &[OtOp]) -> String {
+    let chars: Vec<char> = base.chars().collect();
+    let mut pos = 0usize;
+    let mut out = String::with_capacity(base.len());
+    for op in ops {
+        match op {
+            OtOp::Retain(n) => {
+                out.extend(&chars[pos..pos + n]);
+                pos += n;
+            }
+            OtOp::Insert(s) => out.push_str(s),
+            OtOp::Delete(n) => pos += n,
+        }
+    }
+    out.extend(&chars[pos..]);
+    out
+}
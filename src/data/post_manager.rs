@@ -0,0 +1,190 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use crate::data::model::Post;
+use crate::data::ot::{self, OtEdit};
+use crate::data::post_store::{FilePostStore, PostStore};
+use crate::data::posts_datasource::{PostDataSource, PostsSnapshot};
+
+/// Why an edit ([`PostManager::apply_edit`]) could not be applied.
+#[derive(Debug)]
+pub enum ApplyEditError {
+    NotFound,
+    Stale,
+    Io(anyhow::Error),
+}
+
+/// Keeps an in-memory cache of the current timeline in front of a
+/// pluggable [`PostStore`] so reads stay as cheap as the old
+/// `Arc<Mutex<Vec<Post>>>`, while layering revisions and live-update
+/// broadcast on top.
+///
+/// Every mutation writes through the store before it is reflected in the
+/// cache and published to subscribers, so a crash between the two never
+/// loses data: the store remains the source of truth for the next
+/// [`PostManager::load_with_store`].
+pub struct PostManager {
+    /// Identifies this manager's markdown render cache (see
+    /// [`crate::views::markdown::render_post_markdown`]) so the home
+    /// timeline and every room -- each with its own independently
+    /// numbered posts -- don't collide on post id alone.
+    store_id: u64,
+    store: Arc<dyn PostStore>,
+    posts: Arc<Mutex<Vec<Post>>>,
+    source: PostDataSource,
+    /// Next id to assign a new post, seeded from one past the highest id
+    /// loaded from the store (mirroring `Users::load`'s `max_id + 1`) so a
+    /// restart never reissues an id that's already on disk.
+    next_id: Mutex<u64>,
+}
+
+/// Assigns each [`PostManager`] a process-unique id, so per-store caches
+/// keyed off it never collide even though every manager's own post ids
+/// restart from 1.
+fn next_store_id() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    COUNTER.fetch_add(1, Ordering::Relaxed)
+}
+
+impl PostManager {
+    /// Convenience constructor over the default backend: one JSON file
+    /// per post under `data_dir`. Use [`PostManager::load_with_store`]
+    /// directly to select a different [`PostStore`], e.g.
+    /// `MemoryPostStore` for tests or `SqlPostStore` for a real deploy.
+    pub async fn load(data_dir: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let store = Arc::new(FilePostStore::new(data_dir).await?);
+        Self::load_with_store(store).await
+    }
+
+    /// Loads every post known to `store` into memory, sorted by id, then
+    /// starts a [`PostDataSource`] over the resulting cache.
+    pub async fn load_with_store(store: Arc<dyn PostStore>) -> anyhow::Result<Self> {
+        let mut posts = store.list().await?;
+        posts.sort_by_key(|post| post.id);
+
+        let max_id = posts.iter().map(|post| post.id).max().unwrap_or(0);
+
+        let posts = Arc::new(Mutex::new(posts));
+        let source = PostDataSource::new(posts.clone());
+        source.publish(posts.lock().await.clone());
+
+        Ok(PostManager {
+            store_id: next_store_id(),
+            store,
+            posts,
+            source,
+            next_id: Mutex::new(max_id + 1),
+        })
+    }
+
+    /// This manager's process-unique id, for keying per-store caches (see
+    /// the `store_id` field doc) off something that never collides across
+    /// managers the way post ids do.
+    pub fn store_id(&self) -> u64 {
+        self.store_id
+    }
+
+    /// Allocates the next id for a new post, continuing on from the
+    /// highest id this manager loaded from disk rather than restarting at
+    /// `1` every time the process does.
+    pub async fn next_id(&self) -> u64 {
+        let mut next_id = self.next_id.lock().await;
+        let id = *next_id;
+        *next_id += 1;
+        id
+    }
+
+    /// Shared handle to the underlying post list, for callers that still
+    /// need to lock it directly.
+    pub fn posts(&self) -> Arc<Mutex<Vec<Post>>> {
+        self.posts.clone()
+    }
+
+    /// The watch receiver views subscribe to for live updates.
+    pub fn receiver(&self) -> tokio::sync::watch::Receiver<PostsSnapshot> {
+        self.source.receiver.clone()
+    }
+
+    /// Appends `post` to the timeline, persisting it to the store before
+    /// it becomes visible to readers.
+    pub async fn create_post(&self, post: Post) -> anyhow::Result<()> {
+        self.store.insert(&post).await?;
+        let mut posts_lock = self.posts.lock().await;
+        posts_lock.push(post);
+        self.source.publish(posts_lock.clone());
+        Ok(())
+    }
+
+    /// Applies `mutate` to the post with the given id, if it exists,
+    /// persisting and publishing the result.
+    pub async fn update_post(
+        &self,
+        id: u64,
+        mutate: impl FnOnce(&mut Post),
+    ) -> anyhow::Result<Option<Post>> {
+        let mut posts_lock = self.posts.lock().await;
+        let Some(post) = posts_lock.iter_mut().find(|p| p.id == id) else {
+            return Ok(None);
+        };
+        let mut updated = post.clone();
+        mutate(&mut updated);
+        self.store.update(&updated).await?;
+        *post = updated.clone();
+        self.source.publish(posts_lock.clone());
+        Ok(Some(updated))
+    }
+
+    /// Removes the post with the given id, persisting the removal before
+    /// it is reflected in the cache and published to subscribers -- who
+    /// see it disappear from the next snapshot's diff as a deletion, the
+    /// same as any other mutation.
+    ///
+    /// Returns `false` if no post with that id exists.
+    pub async fn delete_post(&self, id: u64) -> anyhow::Result<bool> {
+        let mut posts_lock = self.posts.lock().await;
+        let Some(index) = posts_lock.iter().position(|p| p.id == id) else {
+            return Ok(false);
+        };
+        self.store.delete(id).await?;
+        posts_lock.remove(index);
+        self.source.publish(posts_lock.clone());
+        Ok(true)
+    }
+
+    /// Applies `edit` to the post with the given id, as a plain
+    /// compare-and-swap against its revision: `edit.base_revision` must
+    /// match the post's current revision exactly, or the edit is rejected
+    /// with [`ApplyEditError::Stale`] so the caller can refetch the
+    /// current content and retry.
+    ///
+    /// An earlier version of this tried to transform a concurrent edit's
+    /// ops forward against whatever had landed since its base, so two
+    /// edits from the same revision would merge instead of one winning.
+    /// But every caller only ever submits a full-document
+    /// `Delete(len) + Insert(message)` edit rather than a real diff, so
+    /// "transforming" it against a concurrent edit from the same base just
+    /// concatenated both messages instead of reconciling them -- silent
+    /// corruption, not a merge. Without client-submitted granular ops
+    /// there's nothing real to transform, so this rejects any edit whose
+    /// base isn't bit-for-bit current instead of pretending to merge.
+    pub async fn apply_edit(&self, id: u64, edit: OtEdit) -> Result<Post, ApplyEditError> {
+        let mut posts_lock = self.posts.lock().await;
+        let Some(post) = posts_lock.iter_mut().find(|p| p.id == id) else {
+            return Err(ApplyEditError::NotFound);
+        };
+
+        if edit.base_revision != post.revision {
+            return Err(ApplyEditError::Stale);
+        }
+
+        let mut updated = post.clone();
+        updated.message = ot::apply(&post.message, &edit.ops);
+        updated.revision += 1;
+
+        self.store.update(&updated).await.map_err(ApplyEditError::Io)?;
+        *post = updated.clone();
+        self.source.publish(posts_lock.clone());
+        Ok(updated)
+    }
+}
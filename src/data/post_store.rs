@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::data::model::Post;
+
+/// Bumped whenever the persisted post representation changes; rows/files
+/// written under an older version are treated as stale and skipped rather
+/// than trusted. Shared by every [`PostStore`] implementation that
+/// actually persists.
+const STORE_FORMAT_VERSION: u32 = 1;
+
+/// Abstracts where posts live so [`crate::data::post_manager::PostManager`]
+/// can layer revisions and live-update broadcast on top of any backend:
+/// plain memory for tests, JSON-on-disk for the existing single-node
+/// deploy, or a SQL database for a deploy that wants real persistence.
+/// `PostManager` holds a store behind `Arc<dyn PostStore>` and selects the
+/// concrete backend once at startup.
+#[async_trait]
+pub trait PostStore: Send + Sync {
+    /// Every post currently known to the store, unordered -- callers that
+    /// care about order (timeline display) sort the result themselves.
+    async fn list(&self) -> anyhow::Result<Vec<Post>>;
+    async fn get(&self, id: u64) -> anyhow::Result<Option<Post>>;
+    /// Persists a brand new post.
+    async fn insert(&self, post: &Post) -> anyhow::Result<()>;
+    /// Overwrites whatever is stored under `post.id` with `post`.
+    async fn update(&self, post: &Post) -> anyhow::Result<()>;
+    async fn delete(&self, id: u64) -> anyhow::Result<()>;
+}
+
+/// Keeps posts in a `HashMap` guarded by a mutex -- nothing survives a
+/// restart. Useful for tests and for a throwaway deploy that doesn't need
+/// persistence.
+#[derive(Default)]
+pub struct MemoryPostStore {
+    posts: Mutex<HashMap<u64, Post>>,
+}
+
+impl MemoryPostStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl PostStore for MemoryPostStore {
+    async fn list(&self) -> anyhow::Result<Vec<Post>> {
+        Ok(self.posts.lock().await.values().cloned().collect())
+    }
+
+    async fn get(&self, id: u64) -> anyhow::Result<Option<Post>> {
+        Ok(self.posts.lock().await.get(&id).cloned())
+    }
+
+    async fn insert(&self, post: &Post) -> anyhow::Result<()> {
+        self.posts.lock().await.insert(post.id, post.clone());
+        Ok(())
+    }
+
+    async fn update(&self, post: &Post) -> anyhow::Result<()> {
+        self.posts.lock().await.insert(post.id, post.clone());
+        Ok(())
+    }
+
+    async fn delete(&self, id: u64) -> anyhow::Result<()> {
+        self.posts.lock().await.remove(&id);
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredPost {
+    format_version: u32,
+    post: Post,
+}
+
+/// Persists one JSON file per post under `data_dir`. This is the original
+/// `PostManager` persistence from before [`PostStore`] existed, lifted out
+/// behind the trait so it can be swapped for [`SqlPostStore`] (or
+/// [`MemoryPostStore`] in tests) without touching revision/OT/broadcast
+/// logic in [`crate::data::post_manager::PostManager`].
+pub struct FilePostStore {
+    data_dir: PathBuf,
+}
+
+impl FilePostStore {
+    /// Ensures `data_dir` exists. Does not read anything yet -- that
+    /// happens in [`FilePostStore::list`].
+    pub async fn new(data_dir: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let data_dir = data_dir.into();
+        tokio::fs::create_dir_all(&data_dir).await?;
+        Ok(FilePostStore { data_dir })
+    }
+
+    fn post_path(&self, id: u64) -> PathBuf {
+        self.data_dir.join(format!("{id}.json"))
+    }
+
+    async fn read_post_file(path: &Path) -> anyhow::Result<Option<Post>> {
+        let bytes = tokio::fs::read(path).await?;
+        let stored: StoredPost = serde_json::from_slice(&bytes)?;
+        if stored.format_version != STORE_FORMAT_VERSION {
+            return Ok(None);
+        }
+        Ok(Some(stored.post))
+    }
+
+    async fn write_post(&self, post: &Post) -> anyhow::Result<()> {
+        let stored = StoredPost {
+            format_version: STORE_FORMAT_VERSION,
+            post: post.clone(),
+        };
+        let bytes = serde_json::to_vec_pretty(&stored)?;
+        tokio::fs::write(self.post_path(post.id), bytes).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PostStore for FilePostStore {
+    async fn list(&self) -> anyhow::Result<Vec<Post>> {
+        let mut posts = Vec::new();
+        let mut entries = tokio::fs::read_dir(&self.data_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            match Self::read_post_file(&path).await {
+                Ok(Some(post)) => posts.push(post),
+                Ok(None) => warn!("skipping stale post cache entry: {}", path.display()),
+                Err(err) => warn!("skipping corrupt post file {}: {}", path.display(), err),
+            }
+        }
+        Ok(posts)
+    }
+
+    async fn get(&self, id: u64) -> anyhow::Result<Option<Post>> {
+        let path = self.post_path(id);
+        if !tokio::fs::try_exists(&path).await? {
+            return Ok(None);
+        }
+        Self::read_post_file(&path).await
+    }
+
+    async fn insert(&self, post: &Post) -> anyhow::Result<()> {
+        self.write_post(post).await
+    }
+
+    async fn update(&self, post: &Post) -> anyhow::Result<()> {
+        self.write_post(post).await
+    }
+
+    async fn delete(&self, id: u64) -> anyhow::Result<()> {
+        let path = self.post_path(id);
+        if tokio::fs::try_exists(&path).await? {
+            tokio::fs::remove_file(path).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Persists posts to a SQLite database via `sqlx`, for a deploy that wants
+/// real queryable persistence rather than one JSON file per post.
+pub struct SqlPostStore {
+    pool: sqlx::SqlitePool,
+}
+
+impl SqlPostStore {
+    /// Connects to `database_url` (e.g. `sqlite://data/posts.db`),
+    /// creating the database file and `posts` table if either is missing.
+    pub async fn connect(database_url: &str) -> anyhow::Result<Self> {
+        let options = sqlx::sqlite::SqliteConnectOptions::from_str(database_url)?
+            .create_if_missing(true);
+        let pool = sqlx::SqlitePool::connect_with(options).await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS posts (
+                id INTEGER PRIMARY KEY,
+                format_version INTEGER NOT NULL,
+                data TEXT NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        Ok(SqlPostStore { pool })
+    }
+
+    async fn upsert(&self, post: &Post) -> anyhow::Result<()> {
+        let data = serde_json::to_string(post)?;
+        sqlx::query(
+            "INSERT INTO posts (id, format_version, data) VALUES (?, ?, ?)
+             ON CONFLICT(id) DO UPDATE SET format_version = excluded.format_version, data = excluded.data",
+        )
+        .bind(post.id as i64)
+        .bind(STORE_FORMAT_VERSION as i64)
+        .bind(data)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PostStore for SqlPostStore {
+    async fn list(&self) -> anyhow::Result<Vec<Post>> {
+        let rows: Vec<(i64, i64, String)> =
+            sqlx::query_as("SELECT id, format_version, data FROM posts")
+                .fetch_all(&self.pool)
+                .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id, format_version, data)| {
+                if format_version as u32 != STORE_FORMAT_VERSION {
+                    warn!("skipping stale post row {id}: format_version {format_version}");
+                    return None;
+                }
+                match serde_json::from_str(&data) {
+                    Ok(post) => Some(post),
+                    Err(err) => {
+                        warn!("skipping corrupt post row {id}: {err}");
+                        None
+                    }
+                }
+            })
+            .collect())
+    }
+
+    async fn get(&self, id: u64) -> anyhow::Result<Option<Post>> {
+        let row: Option<(i64, String)> =
+            sqlx::query_as("SELECT format_version, data FROM posts WHERE id = ?")
+                .bind(id as i64)
+                .fetch_optional(&self.pool)
+                .await?;
+        let Some((format_version, data)) = row else {
+            return Ok(None);
+        };
+        if format_version as u32 != STORE_FORMAT_VERSION {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&data)?))
+    }
+
+    async fn insert(&self, post: &Post) -> anyhow::Result<()> {
+        self.upsert(post).await
+    }
+
+    async fn update(&self, post: &Post) -> anyhow::Result<()> {
+        self.upsert(post).await
+    }
+
+    async fn delete(&self, id: u64) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM posts WHERE id = ?")
+            .bind(id as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -1,63 +1,108 @@
-use std::hash::{DefaultHasher, Hash, Hasher};
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
-use std::time::Duration;
 use tokio::sync::Mutex;
-use tokio::task::JoinSet;
 use crate::data::model::Post;
 
+/// A versioned snapshot of the post timeline broadcast to subscribers.
+///
+/// The `version` field lets a client that reconnects after missing one or
+/// more updates tell that it is behind, without having to diff the post
+/// list itself.
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct PostsSnapshot {
+    pub version: u64,
+    pub posts: Vec<Post>,
+}
+
+/// Owns the shared post list and notifies subscribers the moment it changes.
+///
+/// Unlike the previous implementation, which polled `posts` on a fixed
+/// interval and hashed the whole `Vec<Post>` to detect changes,
+/// `PostDataSource` is pushed to directly: every mutator (`create_post`,
+/// `update_post`, ...) locks `posts`, applies its change, then calls
+/// [`PostDataSource::publish`] to broadcast the new snapshot immediately.
+/// This removes the per-second wakeup and the hash/clone of the whole feed
+/// on every tick, and makes the SSE stream react the instant a post
+/// changes.
 pub struct PostDataSource {
-    pub receiver: tokio::sync::watch::Receiver<Vec<Post>>,
+    pub posts: Arc<Mutex<Vec<Post>>>,
+    pub receiver: tokio::sync::watch::Receiver<PostsSnapshot>,
+    sender: tokio::sync::watch::Sender<PostsSnapshot>,
+    version: std::sync::atomic::AtomicU64,
 }
 
 impl PostDataSource {
-    /// Creates a new instance of `PostDataSource`, which monitors changes to a shared
-    /// `Vec<Post>` and broadcasts updates to listeners through a `tokio::sync::watch::Receiver`.
-    ///
-    /// # Parameters
-    ///
-    /// - `join_set`: A mutable reference to a `JoinSet` that manages asynchronous tasks.
-    ///   A task will be spawned to monitor changes to the `posts` vector and send updates.
-    /// - `posts`: A reference-counted, thread-safe, asynchronous `Vec<Post>` wrapped in
-    ///   `Arc<Mutex<_>>`. This vector represents the data being monitored for changes.
-    ///
-    /// # Behavior
+    /// Creates a new `PostDataSource` wrapping the given shared post list.
     ///
-    /// This function:
-    /// 1. Spawns an asynchronous task to continuously monitor the `posts` vector for changes.
-    /// 2. Uses a hash of the `posts` data to detect changes.
-    /// 3. Sends updates to the `tokio::sync::watch::Receiver` only when the data changes,
-    ///    avoiding redundant updates.
-    /// 4. Runs the monitoring loop with a one-second interval between checks to avoid busy-waiting.
-    ///
-    /// # Returns
+    /// The returned instance exposes a `tokio::sync::watch::Receiver` whose
+    /// channel starts out holding an empty snapshot that counts as already
+    /// seen, so `changed().await` first resolves on the first
+    /// [`PostDataSource::publish`] call, then again on every later one.
+    pub fn new(posts: Arc<Mutex<Vec<Post>>>) -> Self {
+        let (sender, receiver) = tokio::sync::watch::channel(PostsSnapshot::default());
+
+        PostDataSource {
+            posts,
+            receiver,
+            sender,
+            version: std::sync::atomic::AtomicU64::new(0),
+        }
+    }
+
+    /// Broadcasts the current contents of `posts` to all subscribers,
+    /// bumping the version counter so reconnecting clients can tell
+    /// whether they missed an update.
     ///
-    /// A `PostDataSource` instance that provides a `tokio::sync::watch::Receiver`
-    /// to listen for updates to the `posts` vector.
-    pub fn new(join_set: &mut JoinSet<anyhow::Error>, posts: &Arc<Mutex<Vec<Post>>>) -> Self {
-        let (sender, receiver) = tokio::sync::watch::channel(vec![]);
-        let posts_clone = posts.clone();
-
-        // Spawn a task to monitor changes to `posts` and send updates
-        join_set.spawn(async move {
-            let mut last_hash: u64 = 0; // Track the last sent posts
-            loop {
-                let mut hasher = DefaultHasher::new();
-
-                let posts_lock = posts_clone.lock().await;
-                posts_lock.hash(&mut hasher);
-                let hash = hasher.finish();
-
-                // Only send the posts if they have changed since the last send
-                if hash != last_hash {
-                    sender.send_replace(posts_lock.clone());
-                    last_hash = hash; // Update the last sent posts
-                }
-
-                // Sleep or wait for a signal to avoid busy-waiting
-                tokio::time::sleep(Duration::from_millis(1000)).await;
-            }
-        });
-
-        PostDataSource { receiver }
+    /// Call this right after committing a mutation while still holding (or
+    /// just after releasing) the `posts` lock.
+    pub fn publish(&self, posts: Vec<Post>) {
+        let version = self.version.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+        self.sender.send_replace(PostsSnapshot { version, posts });
+    }
+}
+
+/// A single difference between two post snapshots, as produced by
+/// [`diff_posts`] -- the unit a subscriber turns into one SSE event.
+pub enum PostDelta {
+    Created(Post),
+    Updated(Post),
+    Deleted(u64),
+}
+
+/// Hashes everything [`Post`] derives `Hash` over, so two snapshots of the
+/// same post compare equal iff none of its fields changed.
+fn post_hash(post: &Post) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    post.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Diffs `current` against `previous` (a subscriber's last-seen `id ->
+/// content hash` map) by id plus content hash: an id missing from
+/// `previous` is a creation, a matching id whose hash changed is an
+/// update, and an id from `previous` missing from `current` is a deletion.
+/// Returns the deltas alongside the map to remember for the next diff.
+pub fn diff_posts(previous: &HashMap<u64, u64>, current: &[Post]) -> (Vec<PostDelta>, HashMap<u64, u64>) {
+    let mut deltas = Vec::new();
+    let mut next = HashMap::with_capacity(current.len());
+
+    for post in current {
+        let hash = post_hash(post);
+        match previous.get(&post.id) {
+            None => deltas.push(PostDelta::Created(post.clone())),
+            Some(previous_hash) if *previous_hash != hash => deltas.push(PostDelta::Updated(post.clone())),
+            Some(_) => {}
+        }
+        next.insert(post.id, hash);
     }
+
+    for id in previous.keys() {
+        if !next.contains_key(id) {
+            deltas.push(PostDelta::Deleted(*id));
+        }
+    }
+
+    (deltas, next)
 }
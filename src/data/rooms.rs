@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use dashmap::DashMap;
+use crate::data::post_manager::PostManager;
+
+/// Lazily-created registry of independent post timelines, one per named
+/// room (e.g. `/rooms/:name`).
+///
+/// Each room owns its own [`PostManager`] -- and therefore its own on-disk
+/// directory, in-memory cache, and watch channel -- so posting into one
+/// room never notifies another room's subscribers.
+pub struct Rooms {
+    data_dir: PathBuf,
+    rooms: DashMap<String, Arc<PostManager>>,
+}
+
+impl Rooms {
+    /// Rooms are persisted under `data_dir/<room name>`.
+    pub fn new(data_dir: impl Into<PathBuf>) -> Self {
+        Rooms {
+            data_dir: data_dir.into(),
+            rooms: DashMap::new(),
+        }
+    }
+
+    /// Returns the `PostManager` for `name`, loading it from disk (and
+    /// registering it) on first access.
+    pub async fn get_or_create(&self, name: &str) -> anyhow::Result<Arc<PostManager>> {
+        if let Some(room) = self.rooms.get(name) {
+            return Ok(room.clone());
+        }
+
+        let manager = Arc::new(PostManager::load(self.data_dir.join(name)).await?);
+        Ok(self
+            .rooms
+            .entry(name.to_string())
+            .or_insert(manager)
+            .clone())
+    }
+}
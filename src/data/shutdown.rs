@@ -0,0 +1,67 @@
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+/// Coordinates a clean exit across the background tasks that outlive a
+/// single request: every SSE stream (and any future data task) is spawned
+/// through [`Shutdown::spawn`] instead of `tokio::task::spawn`, and watches
+/// [`Shutdown::token`] to notice a shutdown request instead of running
+/// forever.
+///
+/// `main` cancels the token once a SIGINT/SIGTERM arrives, then calls
+/// [`Shutdown::finish`] to wait for every tracked task to actually exit
+/// before the process does, so SSE clients see a clean disconnect rather
+/// than a severed connection.
+pub struct Shutdown {
+    token: CancellationToken,
+    tasks: Mutex<JoinSet<()>>,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Shutdown {
+            token: CancellationToken::new(),
+            tasks: Mutex::new(JoinSet::new()),
+        }
+    }
+
+    /// A clone of the token background tasks should `select!` against to
+    /// notice a shutdown request.
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Spawns `fut`, tracking it so [`Shutdown::finish`] can wait for it to
+    /// exit before the process does.
+    pub async fn spawn(&self, fut: impl Future<Output = ()> + Send + 'static) {
+        self.tasks.lock().await.spawn(fut);
+    }
+
+    /// Cancels `token` and waits up to `timeout` for every spawned task to
+    /// exit. Tasks that are still running once `timeout` elapses are
+    /// aborted rather than left to block process exit indefinitely.
+    pub async fn finish(&self, timeout: Duration) {
+        self.token.cancel();
+        let mut tasks = self.tasks.lock().await;
+        let drain = async {
+            while tasks.join_next().await.is_some() {}
+        };
+        if tokio::time::timeout(timeout, drain).await.is_err() {
+            warn!(
+                remaining = tasks.len(),
+                "timed out draining background tasks on shutdown; aborting the rest"
+            );
+            tasks.abort_all();
+        }
+    }
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+/// `bcrypt` cost used when hashing a new password. Configurable via
+/// `BCRYPT_COST` (see [`Users::load`]) so tests/dev can trade security for
+/// speed; defaults to `bcrypt::DEFAULT_COST`.
+const DEFAULT_BCRYPT_COST: u32 = bcrypt::DEFAULT_COST;
+
+/// Bumped whenever the on-disk user format changes; files written under an
+/// older version are treated as stale and skipped rather than trusted.
+const USER_FORMAT_VERSION: u32 = 1;
+
+/// A registered account. Only `password_hash` is ever persisted for the
+/// password -- [`Users::signup`] discards the plaintext the moment it's
+/// hashed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct User {
+    pub id: u64,
+    pub username: String,
+    pub password_hash: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredUser {
+    format_version: u32,
+    user: User,
+}
+
+/// Why [`Users::signup`] refused to create an account.
+#[derive(Debug)]
+pub enum SignupError {
+    UsernameTaken,
+    Io(anyhow::Error),
+}
+
+/// Persists accounts to disk as one JSON file per user (mirroring
+/// [`crate::data::post_store::FilePostStore`]), with an in-memory
+/// username index in front so login lookups don't hit disk.
+pub struct Users {
+    data_dir: PathBuf,
+    bcrypt_cost: u32,
+    by_username: RwLock<HashMap<String, User>>,
+    next_id: tokio::sync::Mutex<u64>,
+}
+
+impl Users {
+    /// Loads every account found under `data_dir`, skipping (and warning
+    /// about) any file that fails to parse or whose `format_version` has
+    /// gone stale. `bcrypt_cost` is read from `BCRYPT_COST` if set, else
+    /// defaults to [`DEFAULT_BCRYPT_COST`].
+    pub async fn load(data_dir: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let data_dir = data_dir.into();
+        tokio::fs::create_dir_all(&data_dir).await?;
+
+        let bcrypt_cost = std::env::var("BCRYPT_COST")
+            .ok()
+            .and_then(|cost| cost.parse().ok())
+            .unwrap_or(DEFAULT_BCRYPT_COST);
+
+        let mut by_username = HashMap::new();
+        let mut max_id = 0u64;
+        let mut entries = tokio::fs::read_dir(&data_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let bytes = tokio::fs::read(&path).await?;
+            match serde_json::from_slice::<StoredUser>(&bytes) {
+                Ok(stored) if stored.format_version == USER_FORMAT_VERSION => {
+                    max_id = max_id.max(stored.user.id);
+                    by_username.insert(stored.user.username.clone(), stored.user);
+                }
+                Ok(_) => tracing::warn!("skipping stale user cache entry: {}", path.display()),
+                Err(err) => tracing::warn!("skipping corrupt user file {}: {}", path.display(), err),
+            }
+        }
+
+        Ok(Users {
+            data_dir,
+            bcrypt_cost,
+            by_username: RwLock::new(by_username),
+            next_id: tokio::sync::Mutex::new(max_id + 1),
+        })
+    }
+
+    fn user_path(&self, id: u64) -> PathBuf {
+        self.data_dir.join(format!("{id}.json"))
+    }
+
+    async fn write_user(&self, user: &User) -> anyhow::Result<()> {
+        let stored = StoredUser {
+            format_version: USER_FORMAT_VERSION,
+            user: user.clone(),
+        };
+        let bytes = serde_json::to_vec_pretty(&stored)?;
+        tokio::fs::write(self.user_path(user.id), bytes).await?;
+        Ok(())
+    }
+
+    /// Hashes `password` with bcrypt and persists a new account, rejecting
+    /// the attempt with [`SignupError::UsernameTaken`] if `username` is
+    /// already registered.
+    pub async fn signup(&self, username: &str, password: &str) -> Result<User, SignupError> {
+        let mut by_username = self.by_username.write().await;
+        if by_username.contains_key(username) {
+            return Err(SignupError::UsernameTaken);
+        }
+
+        let password_hash = bcrypt::hash(password, self.bcrypt_cost)
+            .map_err(|err| SignupError::Io(err.into()))?;
+        let id = {
+            let mut next_id = self.next_id.lock().await;
+            let id = *next_id;
+            *next_id += 1;
+            id
+        };
+        let user = User {
+            id,
+            username: username.to_string(),
+            password_hash,
+        };
+        self.write_user(&user).await.map_err(SignupError::Io)?;
+        by_username.insert(user.username.clone(), user.clone());
+        Ok(user)
+    }
+
+    /// Verifies `password` against the stored hash for `username`,
+    /// returning the account on success.
+    pub async fn verify(&self, username: &str, password: &str) -> Option<User> {
+        let user = self.by_username.read().await.get(username)?.clone();
+        bcrypt::verify(password, &user.password_hash)
+            .ok()
+            .filter(|valid| *valid)
+            .map(|_| user)
+    }
+
+    /// Looks up an account by id, for resolving a session cookie back to
+    /// a [`User`].
+    pub async fn get(&self, id: u64) -> Option<User> {
+        self.by_username
+            .read()
+            .await
+            .values()
+            .find(|user| user.id == id)
+            .cloned()
+    }
+}
@@ -3,12 +3,27 @@ pub mod data;
 pub mod controller;
 
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use crate::data::model::Post;
+use axum_extra::extract::cookie::Key;
+use crate::data::media_store::MediaStore;
+use crate::data::post_manager::PostManager;
+use crate::data::posts_datasource::PostsSnapshot;
+use crate::data::rooms::Rooms;
+use crate::data::shutdown::Shutdown;
+use crate::data::users::Users;
 
 #[derive(Clone)]
 pub struct AppState {
-    pub posts: Arc<Mutex<Vec<Post>>>,
-    pub post_receiver: tokio::sync::watch::Receiver<Vec<Post>>,
-    pub next_post_id: Arc<Mutex<u64>>,
-}
\ No newline at end of file
+    pub post_manager: Arc<PostManager>,
+    pub post_receiver: tokio::sync::watch::Receiver<PostsSnapshot>,
+    pub rooms: Arc<Rooms>,
+    pub shutdown: Arc<Shutdown>,
+    pub users: Arc<Users>,
+    pub cookie_key: Key,
+    pub media_store: Arc<dyn MediaStore>,
+}
+
+impl axum::extract::FromRef<AppState> for Key {
+    fn from_ref(state: &AppState) -> Self {
+        state.cookie_key.clone()
+    }
+}
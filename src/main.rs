@@ -5,23 +5,43 @@ mod controller;
 use std::env;
 // region:    --- Modules
 use std::sync::Arc;
+use std::time::Duration;
 use tower_http::services::ServeDir;
 use axum::{Router, routing::get};
+use axum::extract::DefaultBodyLimit;
 use axum::routing::{post, put};
+use axum_extra::extract::cookie::Key;
 use tokio::net::TcpListener;
-use tokio::sync::Mutex;
-use tokio::task::JoinSet;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
-use crate::data::model::Post;
-use crate::data::posts_datasource::PostDataSource;
+use crate::data::media_store::{FileMediaStore, MediaStore, MAX_MEDIA_BYTES};
+use crate::data::post_manager::PostManager;
+use crate::data::post_store::{MemoryPostStore, PostStore, SqlPostStore};
+use crate::data::posts_datasource::PostsSnapshot;
+use crate::data::rooms::Rooms;
+use crate::data::shutdown::Shutdown;
+use crate::data::users::Users;
 // endregion: --- Modules
 
+/// How long [`Shutdown::finish`] waits for in-flight SSE tasks to notice
+/// the cancellation token and exit before the process gives up on them.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
 #[derive(Clone)]
 struct AppState {
-    posts: Arc<Mutex<Vec<Post>>>,
-    post_receiver: tokio::sync::watch::Receiver<Vec<Post>>,
-    next_post_id: Arc<Mutex<u64>>,
+    post_manager: Arc<PostManager>,
+    post_receiver: tokio::sync::watch::Receiver<PostsSnapshot>,
+    rooms: Arc<Rooms>,
+    shutdown: Arc<Shutdown>,
+    users: Arc<Users>,
+    cookie_key: Key,
+    media_store: Arc<dyn MediaStore>,
+}
+
+impl axum::extract::FromRef<AppState> for Key {
+    fn from_ref(state: &AppState) -> Self {
+        state.cookie_key.clone()
+    }
 }
 
 #[tokio::main]
@@ -32,15 +52,33 @@ async fn main() {
         .with_env_filter(EnvFilter::from_default_env())
         .init();
 
-    let posts = Arc::new(Mutex::new(vec![]));
-    let next_post_id = Arc::new(Mutex::new(1u64));
-    let mut join_set = JoinSet::new();
-    let post_data_source = PostDataSource::new(&mut join_set, &posts);
+    let data_dir = env::var("POST_DATA_DIR").unwrap_or_else(|_| "data/posts".to_string());
+    let post_manager = Arc::new(
+        load_post_manager(&data_dir)
+            .await
+            .expect("failed to initialize post store"),
+    );
+    let rooms = Arc::new(Rooms::new(format!("{data_dir}/rooms")));
+    let shutdown = Arc::new(Shutdown::new());
+    let users = Arc::new(
+        Users::load(format!("{data_dir}/users"))
+            .await
+            .expect("failed to load user data directory"),
+    );
+    let media_store = Arc::new(
+        FileMediaStore::new(format!("{data_dir}/media"), "/media")
+            .await
+            .expect("failed to initialize media store"),
+    ) as Arc<dyn MediaStore>;
 
     let app_state = AppState {
-        post_receiver: post_data_source.receiver,
-        posts,
-        next_post_id,
+        post_receiver: post_manager.receiver(),
+        post_manager,
+        rooms,
+        shutdown: shutdown.clone(),
+        users,
+        cookie_key: load_cookie_key(),
+        media_store,
     };
     let current_dir = env::current_dir().unwrap();
     let lib_path = current_dir.join("src/lib");
@@ -50,9 +88,24 @@ async fn main() {
         .route("/", get(controller::home::home))
         .route("/home", get(controller::home::home))
         .route("/home/sse", get(controller::home::home_sse))
+        .route("/feed.xml", get(controller::home::feed))
         .route("/home", post(controller::home::create_post))
-        .route("/posts/:id/edit", get(controller::home::edit_post_form))
-        .route("/posts/:id", put(controller::home::update_post))
+        .route("/signup", post(controller::auth::signup))
+        .route("/login", post(controller::auth::login))
+        .route("/logout", post(controller::auth::logout))
+        .route("/posts/:id/edit", get(controller::home::edit_post))
+        .route("/posts/:id", put(controller::home::update_post).delete(controller::home::delete_post))
+        .route("/rooms/:room", get(controller::rooms::room_home))
+        .route("/rooms/:room", post(controller::rooms::create_room_post))
+        .route("/rooms/:room/sse", get(controller::rooms::room_sse))
+        .route(
+            "/media",
+            post(controller::media::upload_media)
+                .layer(DefaultBodyLimit::max(MAX_MEDIA_BYTES as usize)),
+        )
+        .route("/media/:id", get(controller::media::get_media))
+        .route("/api/posts", get(controller::api::list_posts).post(controller::api::create_post))
+        .route("/api/posts/:id", get(controller::api::get_post))
         .nest_service("/lib", ServeDir::new(lib_path))
         .with_state(app_state);
 
@@ -62,7 +115,73 @@ async fn main() {
     tracing::debug!("listening on {}", listener.local_addr().unwrap());
 
     axum::serve(listener, app.into_make_service())
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            info!("shutdown signal received, draining background tasks");
+            shutdown.finish(SHUTDOWN_TIMEOUT).await;
+        })
         .await
         .unwrap();
     // endregion: --- Start Server
 }
+
+/// Builds the top-level timeline's [`PostManager`] over whichever
+/// [`PostStore`] backend `POST_STORE_BACKEND` selects: `memory` (no
+/// persistence, mainly for trying the app out), `sql` (SQLite, via
+/// `DATABASE_URL` or `<data_dir>/posts.db` by default), or the `file`
+/// default (one JSON file per post under `data_dir`, as before).
+async fn load_post_manager(data_dir: &str) -> anyhow::Result<PostManager> {
+    match env::var("POST_STORE_BACKEND").as_deref() {
+        Ok("memory") => PostManager::load_with_store(Arc::new(MemoryPostStore::new()) as Arc<dyn PostStore>).await,
+        Ok("sql") => {
+            let database_url = env::var("DATABASE_URL")
+                .unwrap_or_else(|_| format!("sqlite://{data_dir}/posts.db"));
+            let store = SqlPostStore::connect(&database_url).await?;
+            PostManager::load_with_store(Arc::new(store) as Arc<dyn PostStore>).await
+        }
+        _ => PostManager::load(data_dir).await,
+    }
+}
+
+/// Builds the key used to sign session cookies from `SESSION_SECRET` (at
+/// least 64 bytes of key material), falling back to a freshly generated
+/// key -- which invalidates every existing session -- so local dev works
+/// without any setup.
+fn load_cookie_key() -> Key {
+    match env::var("SESSION_SECRET") {
+        Ok(secret) if secret.len() >= 64 => Key::from(secret.as_bytes()),
+        _ => {
+            tracing::warn!(
+                "SESSION_SECRET not set (or too short); generating an ephemeral signing key, \
+                 which invalidates sessions on every restart"
+            );
+            Key::generate()
+        }
+    }
+}
+
+/// Resolves on SIGINT (Ctrl+C) or, on Unix, SIGTERM -- the signal a
+/// deploy sends before killing the process.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
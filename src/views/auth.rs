@@ -0,0 +1,72 @@
+use html_node::{html, Node};
+
+/// Renders the signup/login panel shown in place of the tweet composer
+/// when the visitor has no session cookie, so the auth routes added
+/// alongside `CurrentUser` (`/signup`, `/login`) are actually reachable
+/// from the browser rather than only from a curl command.
+///
+/// Both forms post with `hx-swap="none"`: on success the handler sets the
+/// session cookie and responds with an `HX-Refresh` header, which makes
+/// htmx reload the page so it re-renders as the now-logged-in user.
+pub fn auth_forms() -> Node {
+    html! {
+        <div class="row justify-content-center mt-3">
+            <div class="col-5">
+                <h5 class="text-center"> "Log in" </h5>
+                <form hx-post="http://localhost:8080/login" hx-swap="none">
+                    <div class="mb-3">
+                        <label for="loginUsername"> "Username:" </label>
+                        <input
+                            id="loginUsername"
+                            class="form-control"
+                            type="text"
+                            name="username"
+                            required="true"
+                        />
+                    </div>
+                    <div class="mb-3">
+                        <label for="loginPassword"> "Password:" </label>
+                        <input
+                            id="loginPassword"
+                            class="form-control"
+                            type="password"
+                            name="password"
+                            required="true"
+                        />
+                    </div>
+                    <div class="d-grid gap-2 mb-3">
+                        <button type="submit" class="btn btn-primary"> "Log in" </button>
+                    </div>
+                </form>
+            </div>
+            <div class="col-5">
+                <h5 class="text-center"> "Sign up" </h5>
+                <form hx-post="http://localhost:8080/signup" hx-swap="none">
+                    <div class="mb-3">
+                        <label for="signupUsername"> "Username:" </label>
+                        <input
+                            id="signupUsername"
+                            class="form-control"
+                            type="text"
+                            name="username"
+                            required="true"
+                        />
+                    </div>
+                    <div class="mb-3">
+                        <label for="signupPassword"> "Password:" </label>
+                        <input
+                            id="signupPassword"
+                            class="form-control"
+                            type="password"
+                            name="password"
+                            required="true"
+                        />
+                    </div>
+                    <div class="d-grid gap-2 mb-3">
+                        <button type="submit" class="btn btn-outline-primary"> "Sign up" </button>
+                    </div>
+                </form>
+            </div>
+        </div>
+    }
+}
@@ -1,36 +1,57 @@
-use html_node::{html, text, Node};
+use html_node::{html, text, unsafe_text, Node};
+use crate::views::auth::auth_forms;
 use crate::views::layout;
-use tokio::sync::watch::Ref;
+use crate::views::markdown::render_post_markdown;
 use crate::data::model::Post;
 
 /// Generates the HTML content for the home page.
 ///
 /// # Parameters
 ///
-/// - `username`: A string slice representing the username of the current user.
-/// - `posts`: A reference to a vector of `Post` instances wrapped in `Ref`.
-///   This contains the posts to be displayed on the home page.
+/// - `username`: The signed-in caller's username, or `None` if the request
+///   carried no valid session cookie. Drives whether the page shows the
+///   tweet composer (and a logout button) or the signup/login forms --
+///   `create_post` requires a session, so there's no point showing the
+///   composer to a visitor who can't use it.
+/// - `posts`: The posts to be displayed on the home page.
+/// - `store_id`: The owning [`crate::data::post_manager::PostManager`]'s
+///   [`crate::data::post_manager::PostManager::store_id`], so each post's
+///   markdown cache entry is keyed against the right store.
 ///
 /// # Returns
 ///
 /// A `String` containing the generated HTML content for the home page.
-pub fn home_page(username: &str, posts: Ref<Vec<Post>>) -> String {
-    println!("posts {:?}", posts.clone());
+pub fn home_page(username: Option<&str>, posts: &[Post], store_id: u64) -> String {
+    println!("posts {:?}", posts);
     let html_content = layout(html! {
         <body>
             <div class="content">
-                <div hx-ext="morph, sse"
+                <div hx-ext="sse"
                     sse-connect="http://localhost:8080/home/sse"
-                    sse-swap="message"
-                    hx-select=".wrapper"
-                    hx-include="data-query"
-                    hx-swap=r#"morph:{ignoreActiveValue:true,morphStyle:'innerHTML'}"#>
+                    sse-swap="post-created,post-updated,post-deleted"
+                    hx-swap="none">
 
                     <div class="wrapper">
                         <nav class="navbar navbar-dark bg-dark shadow-sm py-0">
                             <div class="container-nav">
                                 <a class="navbar-brand" href="#"> "htmx-twitter" </a>
-                                <span class="navbar-text text-white"> {text!("{}", username)} </span>
+                                {
+                                    match username {
+                                        Some(username) => html! {
+                                            <span class="navbar-text text-white">
+                                                {text!("{}", username)}
+                                                <button
+                                                    class="btn btn-sm btn-outline-light ms-2"
+                                                    hx-post="http://localhost:8080/logout"
+                                                    hx-swap="none"
+                                                > "Log out" </button>
+                                            </span>
+                                        },
+                                        None => html! {
+                                            <span class="navbar-text text-white"> "Not logged in" </span>
+                                        },
+                                    }
+                                }
                             </div>
                         </nav>
 
@@ -42,80 +63,54 @@ pub fn home_page(username: &str, posts: Ref<Vec<Post>>) -> String {
                                         <a href="https://htmx.org"> "htmx" </a>
                                         " and Rust"
                                     </p>
-                                    <div>
-                                        <form hx-post="http://localhost:8080/home" hx-swap="none">
-                                            <input
-                                                data-query
-                                                type="hidden"
-                                                class="form-control"
-                                                name="username"
-                                                readonly="true"
-                                                value={username}
-                                            />
-                                            <div class="mb-3 row">
-                                                <label for="txtMessage"> "Message:"  </label>
-                                                <textarea
-                                                    id="txtMessage"
-                                                    class="form-control"
-                                                    rows="3"
-                                                    name="message"
-                                                    required="true"
-                                                > </textarea>
-                                            </div>
-                                            <div class="d-grid gap-2 col-3 mx-auto mb-3">
-                                                <button
-                                                    type="submit"
-                                                    class="btn btn-primary text-center"
-                                                > "Tweet" </button>
-                                            </div>
-                                        </form>
-                                    </div>
-
                                     {
-                                        if posts.is_empty() {
-                                            html! {""}
-                                        } else {
-                                            Node::from(posts.iter().map(|post| {
-                                                html! {
-                                                    <div>
-                                                        <div class="card mb-2 shadow-sm" id={format!("post-{}", post.id)}>
-                                                            <div class="card-body">
-                                                                <div class="d-flex">
-                                                                    <img class="me-4" src={text!("{}", post.avatar.to_string())} width="108" />
-                                                                    <div class="flex-grow-1">
-                                                                        <h5 class="card-title text-muted">
-                                                                            {text!("{}: ", post.username)}
-                                                                            <small> {text!("{}", post.time)} </small>
-                                                                        </h5>
-                                                                        <div class="card-text lead mb-2" id={format!("post-content-{}", post.id)}>
-                                                                            {text!("{}", post.message.to_string())}
-                                                                        </div>
-                                                                        {
-                                                                            if post.username == username || post.owner_id == username {
-                                                                                html! {
-                                                                                    <button 
-                                                                                        class="btn btn-sm btn-outline-secondary"
-                                                                                        hx-get={format!("http://localhost:8080/posts/{}/edit", post.id)}
-                                                                                        hx-target={format!("#post-content-{}", post.id)}
-                                                                                        hx-swap="outerHTML"
-                                                                                    >
-                                                                                        "Edit"
-                                                                                    </button>
-                                                                                }
-                                                                            } else {
-                                                                                html! {""}
-                                                                            }
-                                                                        }
-                                                                    </div>
-                                                                </div>
-                                                            </div>
+                                        match username {
+                                            Some(username) => html! {
+                                                <div>
+                                                    <form hx-post="http://localhost:8080/home" hx-swap="none">
+                                                        <input
+                                                            data-query
+                                                            type="hidden"
+                                                            class="form-control"
+                                                            name="username"
+                                                            readonly="true"
+                                                            value={username}
+                                                        />
+                                                        <div class="mb-3 row">
+                                                            <label for="txtMessage"> "Message:"  </label>
+                                                            <textarea
+                                                                id="txtMessage"
+                                                                class="form-control"
+                                                                rows="3"
+                                                                name="message"
+                                                                required="true"
+                                                            > </textarea>
+                                                        </div>
+                                                        <div class="d-grid gap-2 col-3 mx-auto mb-3">
+                                                            <button
+                                                                type="submit"
+                                                                class="btn btn-primary text-center"
+                                                            > "Tweet" </button>
                                                         </div>
-                                                    </div>
-                                                }
-                                            }))
+                                                    </form>
+                                                </div>
+                                            },
+                                            None => auth_forms(),
                                         }
                                     }
 
+                                    <div id="post-list">
+                                        {
+                                            if posts.is_empty() {
+                                                html! {""}
+                                            } else {
+                                                Node::from(posts.iter().map(|post| {
+                                                    unsafe_text!("{}", post_html(post, store_id))
+                                                }))
+                                            }
+                                        }
+                                    </div>
+
                                 </main>
                             </div>
                         </div>
@@ -127,52 +122,131 @@ pub fn home_page(username: &str, posts: Ref<Vec<Post>>) -> String {
     html_content.to_string()
 }
 
-/// Generates HTML for a single post card with edit functionality.
+/// Generates the canonical HTML for a single post card, including its
+/// content div (`id="post-content-{id}"`) and edit button. Ownership of
+/// the `Edit` action is enforced server-side by `update_post`'s `403`
+/// check rather than by hiding the button here, since this same markup is
+/// reused for SSE broadcasts where no single viewer identity applies.
 ///
 /// # Parameters
 ///
 /// - `post`: The post to render.
-/// - `current_user`: The current user's username for ownership checks.
+/// - `store_id`: The owning `PostManager`'s `store_id`; see [`home_page`]'s
+///   parameter doc.
 ///
 /// # Returns
 ///
 /// A `String` containing the HTML for the post card.
-pub fn post_card(post: &Post, current_user: &str) -> String {
-    let can_edit = post.username == current_user || post.owner_id == current_user;
-    
+pub fn post_html(post: &Post, store_id: u64) -> String {
     html! {
         <div class="card mb-2 shadow-sm" id={format!("post-{}", post.id)}>
-            <div class="card-body">
-                <div class="d-flex">
-                    <img class="me-4" src={text!("{}", post.avatar.to_string())} width="108" />
-                    <div class="flex-grow-1">
-                        <h5 class="card-title text-muted">
-                            {text!("{}: ", post.username)}
-                            <small> {text!("{}", post.time)} </small>
-                        </h5>
-                        <div class="card-text lead mb-2" id={format!("post-content-{}", post.id)}>
-                            {text!("{}", post.message.to_string())}
-                        </div>
-                        {
-                            if can_edit {
-                                html! {
-                                    <button 
-                                        class="btn btn-sm btn-outline-secondary"
-                                        hx-get={format!("http://localhost:8080/posts/{}/edit", post.id)}
-                                        hx-target={format!("#post-content-{}", post.id)}
-                                        hx-swap="outerHTML"
-                                    >
-                                        "Edit"
-                                    </button>
-                                }
-                            } else {
-                                html! {""}
-                            }
-                        }
+            {post_card_body(post, store_id)}
+        </div>
+    }.to_string()
+}
+
+/// The markup shared by [`post_html`] and [`post_created_oob`] -- the
+/// part of the card that doesn't change based on how it's being swapped
+/// in.
+fn post_card_body(post: &Post, store_id: u64) -> Node {
+    html! {
+        <div class="card-body">
+            <div class="d-flex">
+                <img class="me-4" src={text!("{}", post.avatar.to_string())} width="108" />
+                <div class="flex-grow-1">
+                    <h5 class="card-title text-muted">
+                        {text!("{}: ", post.username)}
+                        <small> {text!("{}", post.time)} </small>
+                    </h5>
+                    <div class="card-text lead mb-2" id={format!("post-content-{}", post.id)}>
+                        {unsafe_text!("{}", render_post_markdown(store_id, post.id, &post.message))}
                     </div>
+                    <button
+                        class="btn btn-sm btn-outline-secondary"
+                        hx-get={format!("http://localhost:8080/posts/{}/edit", post.id)}
+                        hx-target={format!("#post-content-{}", post.id)}
+                        hx-swap="outerHTML"
+                    >
+                        "Edit"
+                    </button>
+                    <button
+                        class="btn btn-sm btn-outline-danger"
+                        hx-delete={format!("http://localhost:8080/posts/{}", post.id)}
+                        hx-target={format!("#post-{}", post.id)}
+                        hx-swap="outerHTML"
+                        hx-confirm="Delete this post?"
+                    >
+                        "Delete"
+                    </button>
                 </div>
             </div>
         </div>
+    }
+}
+
+/// Same card as [`post_html`], wrapped in an element carrying
+/// `hx-swap-oob="beforeend:#post-list"`. htmx drops that wrapper for a
+/// `beforeend` swap, so an SSE `post-created` event appends the card itself
+/// to the post list instead of replacing anything.
+///
+/// # Parameters
+///
+/// - `post`: The newly created post.
+/// - `store_id`: The owning `PostManager`'s `store_id`; see [`home_page`]'s
+///   parameter doc.
+///
+/// # Returns
+///
+/// A `String` containing the out-of-band fragment for the event body.
+pub fn post_created_oob(post: &Post, store_id: u64) -> String {
+    html! {
+        <div hx-swap-oob="beforeend:#post-list">
+            <div class="card mb-2 shadow-sm" id={format!("post-{}", post.id)}>
+                {post_card_body(post, store_id)}
+            </div>
+        </div>
+    }.to_string()
+}
+
+/// Renders just the post's content div, with `hx-swap-oob="true"` so an
+/// SSE `post-updated` event replaces `#post-content-{id}` in place on
+/// every other subscriber's page without touching the rest of the card.
+///
+/// # Parameters
+///
+/// - `post`: The post as it stands after the edit.
+/// - `store_id`: The owning `PostManager`'s `store_id`; see [`home_page`]'s
+///   parameter doc.
+///
+/// # Returns
+///
+/// A `String` containing the out-of-band fragment for the event body.
+pub fn post_updated_oob(post: &Post, store_id: u64) -> String {
+    html! {
+        <div
+            class="card-text lead mb-2"
+            id={format!("post-content-{}", post.id)}
+            hx-swap-oob="true"
+        >
+            {unsafe_text!("{}", render_post_markdown(store_id, post.id, &post.message))}
+        </div>
+    }.to_string()
+}
+
+/// Renders the OOB fragment for an SSE `post-deleted` event: an empty
+/// element matching the post card's id with `hx-swap-oob="delete"`, which
+/// htmx uses to remove the matched element from the page.
+///
+/// # Parameters
+///
+/// - `id`: The id of the deleted post.
+///
+/// # Returns
+///
+/// A `String` containing the out-of-band fragment for the event body.
+pub fn post_deleted_oob(id: u64) -> String {
+    html! {
+        <div id={format!("post-{}", id)} hx-swap-oob="delete"></div>
     }.to_string()
 }
 
@@ -188,11 +262,14 @@ pub fn post_card(post: &Post, current_user: &str) -> String {
 pub fn edit_form(post: &Post) -> String {
     html! {
         <div id={format!("post-content-{}", post.id)}>
-            <form 
+            <form
                 hx-put={format!("http://localhost:8080/posts/{}", post.id)}
                 hx-target={format!("#post-{}", post.id)}
                 hx-swap="outerHTML"
             >
+                <input type="hidden" name="username" value={text!("{}", post.username)} />
+                <input type="hidden" name="base_message" value={text!("{}", post.message)} />
+                <input type="hidden" name="base_revision" value={format!("{}", post.revision)} />
                 <div class="mb-3">
                     <textarea
                         class="form-control"
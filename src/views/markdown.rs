@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Mutex, OnceLock};
+
+/// A rendered post body, cached against the hash of the source message it
+/// was produced from.
+struct CachedRender {
+    message_hash: u64,
+    html: String,
+}
+
+/// Cache key: `(store_id, post_id)`. Every [`crate::data::post_manager::PostManager`]
+/// -- the home timeline and each room alike -- numbers its own posts from
+/// 1, so `post_id` alone would collide across stores; `store_id` (see
+/// [`crate::data::post_manager::PostManager::store_id`]) disambiguates them.
+type CacheKey = (u64, u64);
+
+fn cache() -> &'static Mutex<HashMap<CacheKey, CachedRender>> {
+    static CACHE: OnceLock<Mutex<HashMap<CacheKey, CachedRender>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Renders a post's `message` as sanitized Markdown, returning a trusted
+/// HTML fragment suitable for splicing into a card body with [`unsafe_text!`].
+///
+/// The rendered fragment is cached per `(store_id, post_id)` so that
+/// re-rendering the feed on every SSE tick doesn't re-parse messages that
+/// haven't changed. Raw HTML embedded in the Markdown source is stripped
+/// rather than passed through, since `message` comes from untrusted user
+/// input.
+pub fn render_post_markdown(store_id: u64, post_id: u64, message: &str) -> String {
+    let message_hash = {
+        let mut hasher = DefaultHasher::new();
+        message.hash(&mut hasher);
+        hasher.finish()
+    };
+    let key = (store_id, post_id);
+
+    {
+        let cache = cache().lock().unwrap();
+        if let Some(cached) = cache.get(&key) {
+            if cached.message_hash == message_hash {
+                return cached.html.clone();
+            }
+        }
+    }
+
+    let mut options = comrak::Options::default();
+    options.extension.autolink = true;
+    options.extension.strikethrough = true;
+    options.render.hardbreaks = true;
+    options.render.unsafe_ = false; // Strip raw HTML; message is untrusted input.
+
+    let html = comrak::markdown_to_html(message, &options);
+
+    cache()
+        .lock()
+        .unwrap()
+        .insert(key, CachedRender { message_hash, html: html.clone() });
+
+    html
+}
+
+/// Evicts a post's cached render, e.g. once it's been deleted (so the
+/// entry doesn't linger for the life of the process) or before an id gets
+/// reused (so a stale render under that id can never be served).
+pub fn forget_post(store_id: u64, post_id: u64) {
+    cache().lock().unwrap().remove(&(store_id, post_id));
+}
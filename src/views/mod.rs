@@ -1,5 +1,7 @@
 use html_node::{html, Node};
+pub mod auth;
 pub mod home;
+pub mod markdown;
 
 fn layout(content: Node) -> Node {
     html! {
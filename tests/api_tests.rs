@@ -0,0 +1,180 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use sse_rust_htmx::*;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+async fn test_app_state() -> sse_rust_htmx::AppState {
+    static COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let nonce = COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    let pid = std::process::id();
+    let data_dir = std::env::temp_dir().join(format!("sse-rust-htmx-api-tests-{pid}-{nonce}"));
+    let post_manager = Arc::new(
+        sse_rust_htmx::data::post_manager::PostManager::load(data_dir)
+            .await
+            .unwrap(),
+    );
+    let users = Arc::new(
+        sse_rust_htmx::data::users::Users::load(
+            std::env::temp_dir().join(format!("sse-rust-htmx-api-tests-users-{pid}-{nonce}")),
+        )
+        .await
+        .unwrap(),
+    );
+    let media_store = Arc::new(
+        sse_rust_htmx::data::media_store::FileMediaStore::new(
+            std::env::temp_dir().join(format!("sse-rust-htmx-api-tests-media-{pid}-{nonce}")),
+            "/media",
+        )
+        .await
+        .unwrap(),
+    ) as Arc<dyn sse_rust_htmx::data::media_store::MediaStore>;
+    sse_rust_htmx::AppState {
+        post_receiver: post_manager.receiver(),
+        post_manager,
+        rooms: Arc::new(sse_rust_htmx::data::rooms::Rooms::new(
+            std::env::temp_dir().join(format!("sse-rust-htmx-api-tests-rooms-{pid}-{nonce}")),
+        )),
+        shutdown: Arc::new(sse_rust_htmx::data::shutdown::Shutdown::new()),
+        users,
+        cookie_key: axum_extra::extract::cookie::Key::generate(),
+        media_store,
+    }
+}
+
+fn test_router(app_state: sse_rust_htmx::AppState) -> Router {
+    Router::new()
+        .route("/signup", axum::routing::post(sse_rust_htmx::controller::auth::signup))
+        .route(
+            "/api/posts",
+            axum::routing::get(sse_rust_htmx::controller::api::list_posts)
+                .post(sse_rust_htmx::controller::api::create_post),
+        )
+        .route("/api/posts/:id", axum::routing::get(sse_rust_htmx::controller::api::get_post))
+        .with_state(app_state)
+}
+
+async fn signed_in_cookie(app: Router, username: &str) -> String {
+    let signup_request = Request::builder()
+        .method("POST")
+        .uri("/signup")
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body(Body::from(format!("username={username}&password=correct-horse-battery-staple")))
+        .unwrap();
+    let response = app.oneshot(signup_request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let set_cookie = response
+        .headers()
+        .get(axum::http::header::SET_COOKIE)
+        .unwrap()
+        .to_str()
+        .unwrap();
+    set_cookie.split(';').next().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_create_post_via_json_api_then_list_and_get() {
+    let app_state = test_app_state().await;
+    let app = test_router(app_state);
+    let cookie = signed_in_cookie(app.clone(), "importer").await;
+
+    let create_request = Request::builder()
+        .method("POST")
+        .uri("/api/posts")
+        .header("content-type", "application/json")
+        .header("cookie", &cookie)
+        .body(Body::from(r#"{"content":"Hello from the API","author":"importer"}"#))
+        .unwrap();
+
+    let create_response = app.clone().oneshot(create_request).await.unwrap();
+    assert_eq!(create_response.status(), StatusCode::CREATED);
+    let body = axum::body::to_bytes(create_response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let created: sse_rust_htmx::data::model::Post = serde_json::from_slice(&body).unwrap();
+    assert_eq!(created.message, "Hello from the API");
+    assert_eq!(created.username, "importer");
+
+    let list_request = Request::builder()
+        .method("GET")
+        .uri("/api/posts")
+        .body(Body::empty())
+        .unwrap();
+    let list_response = app.clone().oneshot(list_request).await.unwrap();
+    assert_eq!(list_response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(list_response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let posts: Vec<sse_rust_htmx::data::model::Post> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(posts.len(), 1);
+
+    let get_request = Request::builder()
+        .method("GET")
+        .uri(format!("/api/posts/{}", created.id))
+        .body(Body::empty())
+        .unwrap();
+    let get_response = app.oneshot(get_request).await.unwrap();
+    assert_eq!(get_response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(get_response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let fetched: sse_rust_htmx::data::model::Post = serde_json::from_slice(&body).unwrap();
+    assert_eq!(fetched.id, created.id);
+}
+
+#[tokio::test]
+async fn test_create_post_via_json_api_rejects_empty_content() {
+    let app_state = test_app_state().await;
+    let app = test_router(app_state);
+    let cookie = signed_in_cookie(app.clone(), "importer").await;
+
+    let create_request = Request::builder()
+        .method("POST")
+        .uri("/api/posts")
+        .header("content-type", "application/json")
+        .header("cookie", &cookie)
+        .body(Body::from(r#"{"content":"","author":"importer"}"#))
+        .unwrap();
+
+    let create_response = app.oneshot(create_request).await.unwrap();
+    assert_eq!(create_response.status(), StatusCode::BAD_REQUEST);
+    let body = axum::body::to_bytes(create_response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    assert!(body.contains("\"error\""));
+}
+
+#[tokio::test]
+async fn test_create_post_via_json_api_requires_authentication() {
+    let app_state = test_app_state().await;
+    let app = test_router(app_state);
+
+    let create_request = Request::builder()
+        .method("POST")
+        .uri("/api/posts")
+        .header("content-type", "application/json")
+        .body(Body::from(r#"{"content":"Hello","author":"nobody"}"#))
+        .unwrap();
+
+    let create_response = app.oneshot(create_request).await.unwrap();
+    assert_eq!(create_response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_get_nonexistent_post_via_json_api() {
+    let app_state = test_app_state().await;
+    let app = test_router(app_state);
+
+    let get_request = Request::builder()
+        .method("GET")
+        .uri("/api/posts/999")
+        .body(Body::empty())
+        .unwrap();
+
+    let get_response = app.oneshot(get_request).await.unwrap();
+    assert_eq!(get_response.status(), StatusCode::NOT_FOUND);
+}
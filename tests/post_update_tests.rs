@@ -5,35 +5,94 @@ use axum::{
 };
 use sse_rust_htmx::*;
 use std::sync::Arc;
-use tokio::sync::Mutex;
 use tower::ServiceExt;
 
-#[tokio::test]
-async fn test_create_and_update_post() {
-    // Setup test app
-    let posts = Arc::new(Mutex::new(vec![]));
-    let mut join_set = tokio::task::JoinSet::new();
-    let post_data_source = sse_rust_htmx::data::posts_datasource::PostDataSource::new(&mut join_set, &posts);
-    
-    let app_state = sse_rust_htmx::AppState {
-        post_receiver: post_data_source.receiver,
-        posts: posts.clone(),
-    };
-
-    let app = Router::new()
+async fn test_app_state() -> sse_rust_htmx::AppState {
+    static COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let nonce = COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    let pid = std::process::id();
+    let data_dir = std::env::temp_dir().join(format!("sse-rust-htmx-tests-{pid}-{nonce}"));
+    let post_manager = Arc::new(
+        sse_rust_htmx::data::post_manager::PostManager::load(data_dir)
+            .await
+            .unwrap(),
+    );
+    let users = Arc::new(
+        sse_rust_htmx::data::users::Users::load(
+            std::env::temp_dir().join(format!("sse-rust-htmx-tests-users-{pid}-{nonce}")),
+        )
+        .await
+        .unwrap(),
+    );
+    let media_store = Arc::new(
+        sse_rust_htmx::data::media_store::FileMediaStore::new(
+            std::env::temp_dir().join(format!("sse-rust-htmx-tests-media-{pid}-{nonce}")),
+            "/media",
+        )
+        .await
+        .unwrap(),
+    ) as Arc<dyn sse_rust_htmx::data::media_store::MediaStore>;
+    sse_rust_htmx::AppState {
+        post_receiver: post_manager.receiver(),
+        post_manager,
+        rooms: Arc::new(sse_rust_htmx::data::rooms::Rooms::new(
+            std::env::temp_dir().join(format!("sse-rust-htmx-tests-rooms-{pid}-{nonce}")),
+        )),
+        shutdown: Arc::new(sse_rust_htmx::data::shutdown::Shutdown::new()),
+        users,
+        cookie_key: axum_extra::extract::cookie::Key::generate(),
+        media_store,
+    }
+}
+
+fn test_router(app_state: sse_rust_htmx::AppState) -> Router {
+    Router::new()
         .route("/", axum::routing::get(sse_rust_htmx::controller::home::home))
         .route("/home", axum::routing::get(sse_rust_htmx::controller::home::home))
         .route("/home/sse", axum::routing::get(sse_rust_htmx::controller::home::home_sse))
         .route("/home", axum::routing::post(sse_rust_htmx::controller::home::create_post))
-        .route("/posts/{id}/edit", axum::routing::get(sse_rust_htmx::controller::home::edit_post))
-        .route("/posts/{id}", axum::routing::put(sse_rust_htmx::controller::home::update_post))
-        .with_state(app_state);
+        .route("/signup", axum::routing::post(sse_rust_htmx::controller::auth::signup))
+        .route("/posts/:id/edit", axum::routing::get(sse_rust_htmx::controller::home::edit_post))
+        .route("/posts/:id", axum::routing::put(sse_rust_htmx::controller::home::update_post))
+        .route("/posts/:id", axum::routing::delete(sse_rust_htmx::controller::home::delete_post))
+        .with_state(app_state)
+}
+
+/// Signs up a fresh account on `app` and returns the `Cookie` header value
+/// (just `name=value`, without attributes) for its session, so callers can
+/// attach it to subsequent requests that require [`sse_rust_htmx::controller::auth::CurrentUser`].
+async fn signed_in_cookie(app: Router, username: &str) -> String {
+    let signup_request = Request::builder()
+        .method("POST")
+        .uri("/signup")
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body(Body::from(format!("username={username}&password=correct-horse-battery-staple")))
+        .unwrap();
+    let response = app.oneshot(signup_request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let set_cookie = response
+        .headers()
+        .get(axum::http::header::SET_COOKIE)
+        .unwrap()
+        .to_str()
+        .unwrap();
+    set_cookie.split(';').next().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_create_and_update_post() {
+    // Setup test app
+    let app_state = test_app_state().await;
+    let posts = app_state.post_manager.posts();
+    let app = test_router(app_state);
+    let cookie = signed_in_cookie(app.clone(), "testuser").await;
 
     // Create a post first
     let create_request = Request::builder()
         .method("POST")
         .uri("/home")
         .header("content-type", "application/x-www-form-urlencoded")
+        .header("cookie", &cookie)
         .body(Body::from("username=testuser&message=Original message"))
         .unwrap();
 
@@ -43,7 +102,7 @@ async fn test_create_and_update_post() {
     // Test getting edit form
     let edit_request = Request::builder()
         .method("GET")
-        .uri("/posts/0/edit")
+        .uri("/posts/1/edit")
         .body(Body::empty())
         .unwrap();
 
@@ -53,9 +112,12 @@ async fn test_create_and_update_post() {
     // Test updating the post
     let update_request = Request::builder()
         .method("PUT")
-        .uri("/posts/0")
+        .uri("/posts/1")
         .header("content-type", "application/x-www-form-urlencoded")
-        .body(Body::from("username=testuser&message=Updated message"))
+        .header("cookie", &cookie)
+        .body(Body::from(
+            "username=testuser&message=Updated message&base_message=Original message&base_revision=0",
+        ))
         .unwrap();
 
     let update_response = app.oneshot(update_request).await.unwrap();
@@ -70,30 +132,19 @@ async fn test_create_and_update_post() {
 #[tokio::test]
 async fn test_update_nonexistent_post() {
     // Setup test app
-    let posts = Arc::new(Mutex::new(vec![]));
-    let mut join_set = tokio::task::JoinSet::new();
-    let post_data_source = sse_rust_htmx::data::posts_datasource::PostDataSource::new(&mut join_set, &posts);
-    
-    let app_state = sse_rust_htmx::AppState {
-        post_receiver: post_data_source.receiver,
-        posts,
-    };
-
-    let app = Router::new()
-        .route("/", axum::routing::get(sse_rust_htmx::controller::home::home))
-        .route("/home", axum::routing::get(sse_rust_htmx::controller::home::home))
-        .route("/home/sse", axum::routing::get(sse_rust_htmx::controller::home::home_sse))
-        .route("/home", axum::routing::post(sse_rust_htmx::controller::home::create_post))
-        .route("/posts/{id}/edit", axum::routing::get(sse_rust_htmx::controller::home::edit_post))
-        .route("/posts/{id}", axum::routing::put(sse_rust_htmx::controller::home::update_post))
-        .with_state(app_state);
+    let app_state = test_app_state().await;
+    let app = test_router(app_state);
+    let cookie = signed_in_cookie(app.clone(), "testuser").await;
 
     // Try to update a non-existent post
     let update_request = Request::builder()
         .method("PUT")
         .uri("/posts/999")
         .header("content-type", "application/x-www-form-urlencoded")
-        .body(Body::from("username=testuser&message=Updated message"))
+        .header("cookie", &cookie)
+        .body(Body::from(
+            "username=testuser&message=Updated message&base_message=&base_revision=0",
+        ))
         .unwrap();
 
     let update_response = app.oneshot(update_request).await.unwrap();
@@ -103,29 +154,16 @@ async fn test_update_nonexistent_post() {
 #[tokio::test]
 async fn test_update_with_empty_message() {
     // Setup test app
-    let posts = Arc::new(Mutex::new(vec![]));
-    let mut join_set = tokio::task::JoinSet::new();
-    let post_data_source = sse_rust_htmx::data::posts_datasource::PostDataSource::new(&mut join_set, &posts);
-    
-    let app_state = sse_rust_htmx::AppState {
-        post_receiver: post_data_source.receiver,
-        posts: posts.clone(),
-    };
-
-    let app = Router::new()
-        .route("/", axum::routing::get(sse_rust_htmx::controller::home::home))
-        .route("/home", axum::routing::get(sse_rust_htmx::controller::home::home))
-        .route("/home/sse", axum::routing::get(sse_rust_htmx::controller::home::home_sse))
-        .route("/home", axum::routing::post(sse_rust_htmx::controller::home::create_post))
-        .route("/posts/{id}/edit", axum::routing::get(sse_rust_htmx::controller::home::edit_post))
-        .route("/posts/{id}", axum::routing::put(sse_rust_htmx::controller::home::update_post))
-        .with_state(app_state);
+    let app_state = test_app_state().await;
+    let app = test_router(app_state);
+    let cookie = signed_in_cookie(app.clone(), "testuser").await;
 
     // Create a post first
     let create_request = Request::builder()
         .method("POST")
         .uri("/home")
         .header("content-type", "application/x-www-form-urlencoded")
+        .header("cookie", &cookie)
         .body(Body::from("username=testuser&message=Original message"))
         .unwrap();
 
@@ -135,11 +173,214 @@ async fn test_update_with_empty_message() {
     // Try to update with empty message
     let update_request = Request::builder()
         .method("PUT")
-        .uri("/posts/0")
+        .uri("/posts/1")
         .header("content-type", "application/x-www-form-urlencoded")
-        .body(Body::from("username=testuser&message="))
+        .header("cookie", &cookie)
+        .body(Body::from(
+            "username=testuser&message=&base_message=Original message&base_revision=0",
+        ))
         .unwrap();
 
     let update_response = app.oneshot(update_request).await.unwrap();
     assert_eq!(update_response.status(), StatusCode::BAD_REQUEST);
-}
\ No newline at end of file
+}
+
+/// A `base_revision` other than the one the server has stored -- here one
+/// it has never reached, as a replayed or forged submission would carry --
+/// must be rejected by `update_post`'s compare-and-swap with `409` and the
+/// current, server-side content rather than silently applied over it.
+#[tokio::test]
+async fn test_update_with_stale_revision_returns_conflict() {
+    // Setup test app
+    let app_state = test_app_state().await;
+    let app = test_router(app_state);
+    let cookie = signed_in_cookie(app.clone(), "testuser").await;
+
+    // Create a post first
+    let create_request = Request::builder()
+        .method("POST")
+        .uri("/home")
+        .header("content-type", "application/x-www-form-urlencoded")
+        .header("cookie", &cookie)
+        .body(Body::from("username=testuser&message=Original message"))
+        .unwrap();
+
+    let create_response = app.clone().oneshot(create_request).await.unwrap();
+    assert_eq!(create_response.status(), StatusCode::OK);
+
+    // Submit an edit claiming a revision the server has never reached.
+    let update_request = Request::builder()
+        .method("PUT")
+        .uri("/posts/1")
+        .header("content-type", "application/x-www-form-urlencoded")
+        .header("cookie", &cookie)
+        .body(Body::from(
+            "username=testuser&message=Clobbered message&base_message=Original message&base_revision=5",
+        ))
+        .unwrap();
+
+    let update_response = app.oneshot(update_request).await.unwrap();
+    assert_eq!(update_response.status(), StatusCode::CONFLICT);
+
+    let body = axum::body::to_bytes(update_response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    assert!(body.contains("Original message"));
+}
+
+/// Two edits submitted concurrently from the same `base_revision` must not
+/// both succeed: whichever lands second has to be rejected as a conflict
+/// rather than merged in on top of the first, since every edit here is a
+/// full-document replace rather than a real diff -- "merging" two of them
+/// would concatenate both messages instead of reconciling them.
+#[tokio::test]
+async fn test_concurrent_edits_from_same_revision_reject_the_second() {
+    let app_state = test_app_state().await;
+    let posts = app_state.post_manager.posts();
+    let app = test_router(app_state);
+    let cookie = signed_in_cookie(app.clone(), "testuser").await;
+
+    let create_request = Request::builder()
+        .method("POST")
+        .uri("/home")
+        .header("content-type", "application/x-www-form-urlencoded")
+        .header("cookie", &cookie)
+        .body(Body::from("username=testuser&message=Hello"))
+        .unwrap();
+
+    let create_response = app.clone().oneshot(create_request).await.unwrap();
+    assert_eq!(create_response.status(), StatusCode::OK);
+
+    let first_edit = Request::builder()
+        .method("PUT")
+        .uri("/posts/1")
+        .header("content-type", "application/x-www-form-urlencoded")
+        .header("cookie", &cookie)
+        .body(Body::from(
+            "username=testuser&message=Hello A&base_message=Hello&base_revision=0",
+        ))
+        .unwrap();
+    let first_response = app.clone().oneshot(first_edit).await.unwrap();
+    assert_eq!(first_response.status(), StatusCode::OK);
+
+    // Submitted against the same base_revision as the edit above, as if it
+    // raced it -- this must be rejected, not merged.
+    let second_edit = Request::builder()
+        .method("PUT")
+        .uri("/posts/1")
+        .header("content-type", "application/x-www-form-urlencoded")
+        .header("cookie", &cookie)
+        .body(Body::from(
+            "username=testuser&message=Hello B&base_message=Hello&base_revision=0",
+        ))
+        .unwrap();
+    let second_response = app.oneshot(second_edit).await.unwrap();
+    assert_eq!(second_response.status(), StatusCode::CONFLICT);
+
+    let posts_lock = posts.lock().await;
+    assert_eq!(posts_lock[0].message, "Hello A");
+}
+
+/// A restart must never reissue a post id that's already on disk -- doing
+/// so would let the next created post silently overwrite an existing
+/// post's file and duplicate its id in the in-memory timeline. Simulates a
+/// restart by loading a second `PostManager` over the same data directory
+/// and checking its id counter picked up where the first left off.
+#[tokio::test]
+async fn test_post_id_counter_survives_restart() {
+    let data_dir =
+        std::env::temp_dir().join(format!("sse-rust-htmx-tests-restart-{}", std::process::id()));
+    let _ = tokio::fs::remove_dir_all(&data_dir).await;
+
+    let manager = sse_rust_htmx::data::post_manager::PostManager::load(&data_dir)
+        .await
+        .unwrap();
+    let first_id = manager.next_id().await;
+    manager
+        .create_post(sse_rust_htmx::data::model::Post {
+            id: first_id,
+            username: "restart-test".to_string(),
+            message: "before restart".to_string(),
+            time: String::new(),
+            avatar: String::new(),
+            owner_id: "restart-test".to_string(),
+            revision: 0,
+        })
+        .await
+        .unwrap();
+
+    // Simulate a restart: load a fresh `PostManager` over the same directory.
+    let reloaded = sse_rust_htmx::data::post_manager::PostManager::load(&data_dir)
+        .await
+        .unwrap();
+    let next_id = reloaded.next_id().await;
+    assert_eq!(next_id, first_id + 1);
+
+    tokio::fs::remove_dir_all(&data_dir).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_delete_nonexistent_post() {
+    // Setup test app
+    let app_state = test_app_state().await;
+    let app = test_router(app_state);
+    let cookie = signed_in_cookie(app.clone(), "testuser").await;
+
+    // Try to delete a non-existent post
+    let delete_request = Request::builder()
+        .method("DELETE")
+        .uri("/posts/999")
+        .header("cookie", &cookie)
+        .body(Body::empty())
+        .unwrap();
+
+    let delete_response = app.oneshot(delete_request).await.unwrap();
+    assert_eq!(delete_response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_delete_post_requires_ownership() {
+    // Setup test app
+    let app_state = test_app_state().await;
+    let posts = app_state.post_manager.posts();
+    let app = test_router(app_state);
+    let owner_cookie = signed_in_cookie(app.clone(), "owner").await;
+    let other_cookie = signed_in_cookie(app.clone(), "someone-else").await;
+
+    // Create a post as "owner"
+    let create_request = Request::builder()
+        .method("POST")
+        .uri("/home")
+        .header("content-type", "application/x-www-form-urlencoded")
+        .header("cookie", &owner_cookie)
+        .body(Body::from("username=owner&message=Original message"))
+        .unwrap();
+
+    let create_response = app.clone().oneshot(create_request).await.unwrap();
+    assert_eq!(create_response.status(), StatusCode::OK);
+
+    // A different signed-in user may not delete it
+    let forbidden_request = Request::builder()
+        .method("DELETE")
+        .uri("/posts/1")
+        .header("cookie", &other_cookie)
+        .body(Body::empty())
+        .unwrap();
+
+    let forbidden_response = app.clone().oneshot(forbidden_request).await.unwrap();
+    assert_eq!(forbidden_response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(posts.lock().await.len(), 1);
+
+    // The owner can delete it
+    let delete_request = Request::builder()
+        .method("DELETE")
+        .uri("/posts/1")
+        .header("cookie", &owner_cookie)
+        .body(Body::empty())
+        .unwrap();
+
+    let delete_response = app.oneshot(delete_request).await.unwrap();
+    assert_eq!(delete_response.status(), StatusCode::OK);
+    assert_eq!(posts.lock().await.len(), 0);
+}